@@ -0,0 +1,224 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::{tone_frequency, TONE_VOLUME};
+use rodio::{Sink, Source};
+use std::f32::consts::TAU;
+use std::ops::ControlFlow;
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+use std::time::{Duration, Instant};
+
+// Somewhere the tones of a sort can be played
+pub trait ToneOutput {
+    // value: The value of the bar, used to determine pitch
+    // duration_ms: The duration of the tone in milliseconds
+    // num_bars: The total number of bars, used for pitch normalization
+    fn play(&mut self, value: u32, duration_ms: u64, num_bars: usize);
+}
+
+// Audio output that discards the tones, for machines without a sound card
+// Keeps count of what would have been played
+#[derive(Debug, Default)]
+pub struct NullAudio {
+    pub tones: u64,
+    pub duration_ms: u64,
+}
+
+impl ToneOutput for NullAudio {
+    fn play(&mut self, _value: u32, duration_ms: u64, _num_bars: usize) {
+        self.tones += 1;
+        self.duration_ms += duration_ms;
+    }
+}
+
+// Settings of the tone mixer
+const MIXER_SAMPLE_RATE: u32 = 44_100;
+const MIXER_QUEUE_SIZE: usize = 64; // Tones waiting for the mixer, further tones are dropped
+const MIXER_VOICES: usize = 8; // Tones that can sound at the same time
+const MIXER_STALE_MS: u64 = 50; // Tones older than this when the mixer gets to them are dropped
+const MIXER_POLL_SAMPLES: usize = 64; // How often the mixer checks for new tones
+const MIXER_FADE_SAMPLES: usize = 88; // 2 ms fade in and out to avoid clicks
+
+// A tone sent from the sort to the mixer
+struct ToneRequest {
+    freq: f32,
+    duration_ms: u64,
+    sent: Instant,
+}
+
+// Non-blocking live audio output
+// Tones go through a bounded queue to a voice mixer that runs on the audio thread of the sink,
+// so the speed of the sort no longer depends on the audio. When the sort produces tones
+// faster than they can be heard, stale tones are dropped and tones of the same pitch are merged.
+pub struct ToneMixer {
+    sender: SyncSender<ToneRequest>,
+    pub dropped: u64, // Tones dropped because the queue was full
+}
+
+impl ToneMixer {
+    // Starts mixing on the sink; the mixer stops once the ToneMixer is dropped
+    // sink: The audio sink to play the mixed voices on
+    pub fn new(sink: &Sink) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(MIXER_QUEUE_SIZE);
+        sink.append(MixerSource {
+            receiver,
+            voices: Vec::with_capacity(MIXER_VOICES),
+            until_poll: 0,
+            disconnected: false,
+        });
+        ToneMixer { sender, dropped: 0 }
+    }
+}
+
+impl ToneOutput for ToneMixer {
+    fn play(&mut self, value: u32, duration_ms: u64, num_bars: usize) {
+        let request = ToneRequest {
+            freq: tone_frequency(value, num_bars),
+            duration_ms,
+            sent: Instant::now(),
+        };
+        // Never wait for the mixer
+        if self.sender.try_send(request).is_err() {
+            self.dropped += 1;
+        }
+    }
+}
+
+// A tone that is currently sounding in the mixer
+struct Voice {
+    freq: f32,
+    phase: f32,
+    position: usize, // Samples played so far
+    length: usize,   // Total samples of the tone
+}
+
+impl Voice {
+    fn new(request: &ToneRequest) -> Self {
+        Voice {
+            freq: request.freq,
+            phase: 0.0,
+            position: 0,
+            length: (request.duration_ms * MIXER_SAMPLE_RATE as u64 / 1000) as usize,
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let remaining = self.length - self.position;
+        let envelope = (self.position.min(remaining) as f32 / MIXER_FADE_SAMPLES as f32).min(1.0);
+        let sample = self.phase.sin() * TONE_VOLUME * envelope;
+        self.phase = (self.phase + TAU * self.freq / MIXER_SAMPLE_RATE as f32) % TAU;
+        self.position += 1;
+        sample
+    }
+}
+
+// The rodio source that mixes the voices, pulled by the audio thread of the sink
+struct MixerSource {
+    receiver: Receiver<ToneRequest>,
+    voices: Vec<Voice>,
+    until_poll: usize,  // Samples until the queue is checked again
+    disconnected: bool, // The ToneMixer was dropped, no more tones will arrive
+}
+
+impl MixerSource {
+    // Moves the queued tones into voices
+    fn poll(&mut self) {
+        loop {
+            let request = match self.receiver.try_recv() {
+                Ok(request) => request,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.disconnected = true;
+                    return;
+                }
+            };
+            if request.sent.elapsed() > Duration::from_millis(MIXER_STALE_MS) {
+                // The sort has moved on since, playing it now would only lag behind
+                continue;
+            }
+            if let Some(voice) = self
+                .voices
+                .iter_mut()
+                .find(|voice| (voice.freq - request.freq).abs() < 1.0)
+            {
+                // The same pitch is already sounding, restart it instead of doubling it
+                voice.position = voice.position.min(MIXER_FADE_SAMPLES);
+                voice.length = voice.position + Voice::new(&request).length;
+            } else if self.voices.len() < MIXER_VOICES {
+                self.voices.push(Voice::new(&request));
+            } else if let Some(voice) = self
+                .voices
+                .iter_mut()
+                .min_by_key(|voice| voice.length - voice.position)
+            {
+                // All voices are busy, replace the one closest to its end
+                *voice = Voice::new(&request);
+            }
+        }
+    }
+}
+
+impl Iterator for MixerSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.until_poll == 0 {
+            self.poll();
+            self.until_poll = MIXER_POLL_SAMPLES;
+        }
+        self.until_poll -= 1;
+        if self.disconnected && self.voices.is_empty() {
+            return None;
+        }
+        let sample = self.voices.iter_mut().map(Voice::next_sample).sum::<f32>();
+        self.voices.retain(|voice| voice.position < voice.length);
+        Some(sample.clamp(-1.0, 1.0))
+    }
+}
+
+impl Source for MixerSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        MIXER_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// Observer that plays a tone for every comparison, swap and write
+pub struct ToneObserver<O: ToneOutput> {
+    pub output: O,
+    duration_ms: u64,
+}
+
+impl<O: ToneOutput> ToneObserver<O> {
+    // output: Where the tones are played (e.g. a ToneMixer)
+    // duration_ms: The duration of each tone in milliseconds
+    pub fn new(output: O, duration_ms: u64) -> Self {
+        ToneObserver {
+            output,
+            duration_ms,
+        }
+    }
+}
+
+impl<O: ToneOutput> SortObserver for ToneObserver<O> {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        // The pitch depends on the value of the element that was touched
+        let value = match event {
+            SortEvent::Compare(a, _) | SortEvent::Swap(a, _) | SortEvent::Read(a) => array[a],
+            SortEvent::Write(_, value) => value,
+            SortEvent::AuxCompare(_, _, value, index) => value.min(array[index]),
+            _ => return ControlFlow::Continue(()),
+        };
+        self.output.play(value, self.duration_ms, array.len());
+        ControlFlow::Continue(())
+    }
+}
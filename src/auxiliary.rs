@@ -0,0 +1,197 @@
+use crate::draw::fill_rect;
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, GLYPH_HEIGHT};
+use crate::render::SortView;
+use crate::{HEIGHT, WIDTH};
+use std::ops::ControlFlow;
+
+// Height of the panels below the bars
+const PANEL_HEIGHT: usize = 320;
+const PANEL_MARGIN: usize = 16; // Kept free at the bottom for the timeline
+const LABEL_SCALE: usize = 2;
+const LABEL_HEIGHT: usize = (GLYPH_HEIGHT + 3) * LABEL_SCALE;
+
+const SEPARATOR_COLOR: u32 = 0x404040;
+const AUX_BAR_COLOR: u32 = 0xC0C0C0;
+const AUX_ACCESS_COLOR: u32 = 0xFF0000; // The element written or taken out by the last operation
+const LABEL_COLOR: u32 = 0xFFFFFF;
+
+// How the buffers share the panel area
+enum Layout {
+    // One full-width row per buffer, each with its own name and scale (e.g. counts and buckets)
+    Rows(&'static [&'static str]),
+    // Side by side columns under one title, all drawn at the same scale (e.g. the buckets of Bucket Sort)
+    Columns(&'static str),
+}
+
+// A rectangle of the window
+struct Area {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+// Draws the auxiliary buffers reported by an algorithm (AuxBuffer/AuxWrite/AuxRead events)
+// as bars in panels beneath the main bars, one panel per buffer id.
+// Elements taken out of a buffer (AuxRead) disappear, so the buffers drain as they are used.
+pub struct AuxView {
+    layout: Layout,
+    buffers: Vec<Vec<u32>>,
+    peaks: Vec<u32>, // Largest value ever written to every buffer, the scale of its bars
+    capacities: Vec<usize>, // Largest length every buffer ever had, the width of its bars
+    last_access: Option<(usize, usize, u32)>, // Buffer, index and value touched by the last event
+}
+
+impl AuxView {
+    // One row per buffer
+    // labels: The names of the buffers, by id
+    pub fn new(labels: &'static [&'static str]) -> Self {
+        Self::with_layout(Layout::Rows(labels), labels.len())
+    }
+
+    // Buffers side by side
+    // title: The name shown above the columns
+    // count: The number of buffers
+    pub fn columns(title: &'static str, count: usize) -> Self {
+        Self::with_layout(Layout::Columns(title), count)
+    }
+
+    // Draws the buffers with the bar width and height scale of the main array, for buffers
+    // holding its elements (e.g. the halves copied by Merge Sort)
+    // num_bars: The number of elements being sorted
+    pub fn with_array_scale(mut self, num_bars: usize) -> Self {
+        self.peaks.fill(num_bars.max(1) as u32);
+        self.capacities.fill(num_bars.max(1));
+        self
+    }
+
+    fn with_layout(layout: Layout, count: usize) -> Self {
+        AuxView {
+            layout,
+            buffers: vec![Vec::new(); count],
+            peaks: vec![1; count],
+            capacities: vec![1; count],
+            last_access: None,
+        }
+    }
+
+    // The current contents of the buffer with the id
+    pub fn buffer(&self, id: usize) -> &[u32] {
+        &self.buffers[id]
+    }
+
+    // Draws the values of a buffer as bars standing on the bottom of the area
+    // peak: The value drawn at the full height of the area
+    // capacity: The number of bars that fit the width of the area
+    fn draw_values(&self, buffer: &mut [u32], id: usize, area: &Area, peak: u32, capacity: usize) {
+        let bar_width = (area.width / capacity).max(1);
+        // Wide bars are drawn with a gap to tell neighbours of equal height apart
+        let drawn_width = if bar_width > 3 {
+            bar_width - 1
+        } else {
+            bar_width
+        };
+        for (i, &value) in self.buffers[id].iter().enumerate() {
+            let x = area.x + i * bar_width;
+            if x >= area.x + area.width {
+                break;
+            }
+            let (value, color) = match self.last_access {
+                Some((last_id, last_i, last_value)) if (last_id, last_i) == (id, i) => {
+                    (last_value, AUX_ACCESS_COLOR)
+                }
+                _ => (value, AUX_BAR_COLOR),
+            };
+            let height = (value as u64 * area.height as u64 / peak as u64) as usize;
+            let y = area.y + area.height - height;
+            fill_rect(buffer, x, y, drawn_width, height, color);
+        }
+    }
+}
+
+impl SortObserver for AuxView {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        self.last_access = None;
+        match event {
+            SortEvent::AuxBuffer(id, len) => {
+                self.buffers[id] = vec![0; len];
+                self.capacities[id] = self.capacities[id].max(len);
+            }
+            SortEvent::AuxWrite(id, i, value) => {
+                let values = &mut self.buffers[id];
+                if i >= values.len() {
+                    values.resize(i + 1, 0);
+                    self.capacities[id] = self.capacities[id].max(i + 1);
+                }
+                values[i] = value;
+                self.peaks[id] = self.peaks[id].max(value);
+                self.last_access = Some((id, i, value));
+            }
+            SortEvent::AuxCompare(id, i, value, _) => self.last_access = Some((id, i, value)),
+            SortEvent::AuxRead(id, i) => {
+                // Shown one last time, highlighted, before it disappears
+                self.last_access = Some((id, i, self.buffers[id][i]));
+                self.buffers[id][i] = 0;
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for AuxView {
+    fn draw(&self, buffer: &mut [u32]) {
+        let top = HEIGHT - PANEL_HEIGHT;
+        let height = PANEL_HEIGHT - PANEL_MARGIN;
+        fill_rect(buffer, 0, top, WIDTH, 2, SEPARATOR_COLOR);
+        match self.layout {
+            Layout::Rows(labels) => {
+                let strip_height = height / labels.len();
+                for (id, label) in labels.iter().enumerate() {
+                    let strip_top = top + id * strip_height;
+                    draw_text(buffer, 12, strip_top + 8, label, LABEL_SCALE, LABEL_COLOR);
+                    let bars_top = strip_top + 8 + LABEL_HEIGHT;
+                    let area = Area {
+                        x: 0,
+                        y: bars_top,
+                        width: WIDTH,
+                        height: strip_top + strip_height - bars_top,
+                    };
+                    self.draw_values(buffer, id, &area, self.peaks[id], self.capacities[id]);
+                }
+            }
+            Layout::Columns(title) => {
+                draw_text(buffer, 12, top + 8, title, LABEL_SCALE, LABEL_COLOR);
+                let bars_top = top + 8 + LABEL_HEIGHT;
+                let column_width = WIDTH / self.buffers.len();
+                let peak = self.peaks.iter().copied().max().unwrap_or(1);
+                let capacity = self.capacities.iter().copied().max().unwrap_or(1);
+                for id in 0..self.buffers.len() {
+                    let x = id * column_width;
+                    if id > 0 {
+                        fill_rect(
+                            buffer,
+                            x - 2,
+                            bars_top,
+                            2,
+                            top + height - bars_top,
+                            SEPARATOR_COLOR,
+                        );
+                    }
+                    let area = Area {
+                        x: x + 2,
+                        y: bars_top,
+                        width: column_width - 4,
+                        height: top + height - bars_top,
+                    };
+                    self.draw_values(buffer, id, &area, peak, capacity);
+                }
+            }
+        }
+    }
+
+    fn panel_height(&self) -> usize {
+        PANEL_HEIGHT
+    }
+}
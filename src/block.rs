@@ -0,0 +1,328 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::insertion::insert;
+use crate::merge::{merge_in_place, rotate};
+use std::ops::ControlFlow;
+
+// Length of the runs Insertion Sort builds before the first merge pass
+const INSERTION_RUN: usize = 8;
+
+// Size of the blocks the runs are split into for n elements (about √n)
+pub fn block_size(n: usize) -> usize {
+    n.isqrt().max(1)
+}
+
+// Swaps array[a..a + len] with array[b..b + len], element by element
+fn block_swap(
+    array: &mut [u32],
+    a: usize,
+    b: usize,
+    len: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    for i in 0..len {
+        array.swap(a + i, b + i);
+        observer.on_event(array, SortEvent::Swap(a + i, b + i))?;
+    }
+    ControlFlow::Continue(())
+}
+
+// The first index of the sorted range array[low..high] whose element is not less than array[key]
+fn lower_bound(
+    array: &[u32],
+    low: usize,
+    high: usize,
+    key: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    let (mut first, mut last) = (low, high);
+    while first < last {
+        let probe = first + (last - first) / 2;
+        observer.on_event(array, SortEvent::Compare(probe, key))?;
+        if array[probe] < array[key] {
+            first = probe + 1;
+        } else {
+            last = probe;
+        }
+    }
+    ControlFlow::Continue(first)
+}
+
+// Moves up to `wanted` distinct elements to the front of the array, in sorted order, and
+// returns how many it found. The keys are the first occurrences of their values, so putting
+// them back in front of their equals at the end keeps the sort stable.
+fn collect_keys(
+    array: &mut [u32],
+    wanted: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    let n = array.len();
+    // The keys found so far are array[first..first + count], the block travels along the array
+    let (mut first, mut count) = (0, 1);
+    let mut i = 1;
+    while i < n && count < wanted {
+        let pos = lower_bound(array, first, first + count, i, observer)?;
+        let is_new = pos == first + count || {
+            observer.on_event(array, SortEvent::Compare(pos, i))?;
+            array[pos] != array[i]
+        };
+        if is_new {
+            // Move the keys next to the new one, then insert it among them
+            rotate(array, first, first + count, i, observer)?;
+            let pos = pos + (i - count - first);
+            first = i - count;
+            rotate(array, pos, i, i + 1, observer)?;
+            count += 1;
+            observer.on_event(array, SortEvent::Buffer(first, count))?;
+        }
+        i += 1;
+    }
+    rotate(array, 0, first, first + count, observer)?;
+    observer.on_event(array, SortEvent::Buffer(0, count))?;
+    ControlFlow::Continue(count)
+}
+
+// Merges the sorted runs array[low..mid] and array[mid..high] using the buffer at array[buffer..]
+// as swap space: the left run is swapped into the buffer and merged back from there,
+// so the buffer keeps its elements (in another order). The buffer must hold the left run.
+fn merge_with_buffer(
+    array: &mut [u32],
+    low: usize,
+    mid: usize,
+    high: usize,
+    buffer: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    block_swap(array, low, buffer, mid - low, observer)?;
+    let buffer_end = buffer + (mid - low);
+    // i runs through the left run in the buffer, j through the right run,
+    // and array[k..j] holds the buffer elements waiting to be swapped back
+    let (mut i, mut j, mut k) = (buffer, mid, low);
+    while i < buffer_end && j < high {
+        observer.on_event(array, SortEvent::Compare(i, j))?;
+        // Equal elements are taken from the left run first, which keeps the merge stable
+        if array[j] < array[i] {
+            array.swap(k, j);
+            observer.on_event(array, SortEvent::Swap(k, j))?;
+            j += 1;
+        } else {
+            array.swap(k, i);
+            observer.on_event(array, SortEvent::Swap(k, i))?;
+            i += 1;
+        }
+        k += 1;
+    }
+    // The right run ended first: the rest of the left run goes to the end
+    while i < buffer_end {
+        array.swap(k, i);
+        observer.on_event(array, SortEvent::Swap(k, i))?;
+        i += 1;
+        k += 1;
+    }
+    ControlFlow::Continue(())
+}
+
+// The internal buffer of the keys at the front of the array
+// array[..tags] tags the blocks of the left run, array[tags..end] is swap space for local merges
+struct Keys {
+    tags: usize,
+    end: usize,
+    block: usize,
+}
+
+// Merges the sorted runs A = array[low..mid] and B = array[mid..high] block by block
+// (the merge of WikiSort): A is split into blocks, each marked with a tag from the keys.
+// The A blocks roll through B by block swaps; whenever the smallest A block (found by its tag)
+// belongs before the last B block, it is dropped there by a rotation and the A block dropped
+// before it is merged with the B elements after it, using the keys as swap space.
+fn merge_blocks(
+    array: &mut [u32],
+    low: usize,
+    mid: usize,
+    high: usize,
+    keys: &Keys,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let block = keys.block;
+    let buffer = keys.tags;
+
+    // The blocks of A, after the unevenly sized first one
+    let first_a = (low, low + (mid - low) % block);
+    let (mut a_start, mut a_end) = (first_a.1, mid);
+
+    // Tag every A block: its first element is swapped with a key, in order
+    observer.on_event(array, SortEvent::Phase("Tagging blocks"))?;
+    let mut tag = 0;
+    let mut index = a_start;
+    while index < a_end {
+        array.swap(tag, index);
+        observer.on_event(array, SortEvent::Swap(tag, index))?;
+        tag += 1;
+        index += block;
+    }
+
+    let mut last_a = first_a;
+    // The B elements in front of the rolling A blocks
+    let mut last_b = (low, low);
+    // The next B block
+    let (mut b_start, mut b_end) = (mid, (mid + block).min(high));
+    // The key holding the first element of the smallest remaining A block
+    let mut tag = 0;
+    loop {
+        let drop_a = if last_b.0 < last_b.1 {
+            observer.on_event(array, SortEvent::Compare(last_b.1 - 1, tag))?;
+            array[last_b.1 - 1] >= array[tag]
+        } else {
+            false
+        };
+        if drop_a || b_start == b_end {
+            // The smallest A block goes in front of the B elements not less than its first element
+            let b_split = lower_bound(array, last_b.0, last_b.1, tag, observer)?;
+            let b_remaining = last_b.1 - b_split;
+
+            // Block selection: the smallest tag marks the smallest A block
+            observer.on_event(array, SortEvent::Phase("Block selection"))?;
+            let mut min_a = a_start;
+            let mut find = min_a + block;
+            while find < a_end {
+                observer.on_event(array, SortEvent::Compare(find, min_a))?;
+                if array[find] < array[min_a] {
+                    min_a = find;
+                }
+                find += block;
+            }
+            block_swap(array, a_start, min_a, block, observer)?;
+
+            // Give the tag back to the keys
+            array.swap(a_start, tag);
+            observer.on_event(array, SortEvent::Swap(a_start, tag))?;
+            tag += 1;
+
+            // Merge the previous A block with the B elements that belong in front of this one
+            observer.on_event(array, SortEvent::Phase("Local merge"))?;
+            merge_with_buffer(array, last_a.0, last_a.1, b_split, buffer, observer)?;
+
+            // Rotate the A block in front of the rest of the B elements
+            observer.on_event(array, SortEvent::Phase("Block rotation"))?;
+            rotate(array, b_split, a_start, a_start + block, observer)?;
+
+            last_a = (a_start - b_remaining, a_start - b_remaining + block);
+            last_b = (last_a.1, last_a.1 + b_remaining);
+            a_start += block;
+            if a_start == a_end {
+                break;
+            }
+        } else if b_end - b_start < block {
+            // Rotate the unevenly sized last B block in front of the remaining A blocks
+            observer.on_event(array, SortEvent::Phase("Block rotation"))?;
+            let len = b_end - b_start;
+            rotate(array, a_start, b_start, b_end, observer)?;
+            last_b = (a_start, a_start + len);
+            a_start += len;
+            a_end += len;
+            b_end = b_start;
+        } else {
+            // Roll the leftmost A block to the end by swapping it with the next B block
+            observer.on_event(array, SortEvent::Phase("Rolling blocks"))?;
+            block_swap(array, a_start, b_start, block, observer)?;
+            last_b = (a_start, a_start + block);
+            a_start += block;
+            a_end += block;
+            b_start += block;
+            b_end = (b_end + block).min(high);
+        }
+    }
+
+    // Merge the last A block with the remaining B elements
+    observer.on_event(array, SortEvent::Phase("Local merge"))?;
+    merge_with_buffer(array, last_a.0, last_a.1, high, buffer, observer)
+}
+
+// Merges the sorted runs array[low..mid] and array[mid..high] with the cheapest way available
+fn merge(
+    array: &mut [u32],
+    low: usize,
+    mid: usize,
+    high: usize,
+    keys: &Keys,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    observer.on_event(array, SortEvent::Run(low, mid - low))?;
+    observer.on_event(array, SortEvent::Run(mid, high - mid))?;
+
+    observer.on_event(array, SortEvent::Compare(mid - 1, mid))?;
+    if array[mid - 1] > array[mid] {
+        observer.on_event(array, SortEvent::Compare(low, high - 1))?;
+        let swap_space = keys.end - keys.tags;
+        if array[high - 1] < array[low] {
+            // All of B belongs in front of A
+            observer.on_event(array, SortEvent::Phase("Block rotation"))?;
+            rotate(array, low, mid, high, observer)?;
+        } else if mid - low <= swap_space {
+            observer.on_event(array, SortEvent::Phase("Local merge"))?;
+            merge_with_buffer(array, low, mid, high, keys.tags, observer)?;
+        } else if keys.block > 0 && (mid - low) / keys.block <= keys.tags {
+            merge_blocks(array, low, mid, high, keys, observer)?;
+        } else {
+            // Too few keys to tag the blocks: merge by rotations instead
+            observer.on_event(array, SortEvent::Phase("Rotation merge"))?;
+            merge_in_place(array, low, mid, high, observer)?;
+        }
+    }
+    observer.on_event(array, SortEvent::Run(low, high - low))
+}
+
+// Implements Block Sort (a WikiSort style block merge sort), reporting every step to the observer
+// A stable merge sort without extra memory: it collects about 2√n distinct keys at the front
+// of the array as an internal buffer, sorts the rest bottom-up with block merges that use the
+// keys as tags and swap space, then sorts the keys and merges them back in
+pub fn block_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    if n < 2 {
+        return observer.on_event(array, SortEvent::Done);
+    }
+
+    observer.on_event(array, SortEvent::Phase("Collecting keys"))?;
+    let block = block_size(n);
+    let count = collect_keys(array, 2 * block, observer)?;
+    let tags = count / 2;
+    let keys = Keys {
+        tags,
+        end: count,
+        block: block.min(count - tags),
+    };
+
+    observer.on_event(array, SortEvent::Phase("Insertion Sort"))?;
+    let mut low = count;
+    while low < n {
+        let high = (low + INSERTION_RUN).min(n);
+        for i in low + 1..high {
+            insert(array, low, i, observer)?;
+        }
+        observer.on_event(array, SortEvent::Run(low, high - low))?;
+        low = high;
+    }
+
+    let mut width = INSERTION_RUN;
+    while width < n - count {
+        let mut low = count;
+        while low + width < n {
+            let mid = low + width;
+            let high = (mid + width).min(n);
+            merge(array, low, mid, high, &keys, observer)?;
+            low = high;
+        }
+        width *= 2;
+    }
+
+    // The local merges mixed up the swap space: sort the keys again and merge them back in
+    observer.on_event(array, SortEvent::Phase("Redistributing buffer"))?;
+    for i in 1..count {
+        insert(array, 0, i, observer)?;
+    }
+    observer.on_event(array, SortEvent::Buffer(0, 0))?;
+    merge_in_place(array, 0, count, n, observer)?;
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,76 @@
+use crate::budget::{Budget, OPERATION_BUDGET};
+use crate::events::{SortEvent, SortObserver};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::ops::ControlFlow;
+
+// Whether the array is sorted, checking neighbouring elements until one pair is out of order
+fn is_sorted(array: &[u32], observer: &mut dyn SortObserver) -> ControlFlow<(), bool> {
+    for i in 1..array.len() {
+        observer.on_event(array, SortEvent::Compare(i - 1, i))?;
+        if array[i - 1] > array[i] {
+            return ControlFlow::Continue(false);
+        }
+    }
+    ControlFlow::Continue(true)
+}
+
+// Shuffles the array until it happens to be sorted
+fn bogo_sort(
+    array: &mut [u32],
+    rng: &mut StdRng,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    while !is_sorted(array, observer)? {
+        // Fisher-Yates shuffle
+        for i in (1..array.len()).rev() {
+            let j = rng.random_range(0..=i);
+            array.swap(i, j);
+            observer.on_event(array, SortEvent::Swap(i, j))?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+// Swaps two random elements until the array happens to be sorted
+fn bozo_sort(
+    array: &mut [u32],
+    rng: &mut StdRng,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    while !is_sorted(array, observer)? {
+        let i = rng.random_range(0..array.len());
+        let j = rng.random_range(0..array.len());
+        array.swap(i, j);
+        observer.on_event(array, SortEvent::Swap(i, j))?;
+    }
+    ControlFlow::Continue(())
+}
+
+// Implements Bogosort, reporting every step to the observer
+// Expects n! shuffles of the array before it is sorted.
+// Gives up when it runs out of its operation budget.
+// seed: Seeds the RNG of the shuffles, so runs can be repeated
+pub fn bogo_sort_visualized(
+    array: &mut [u32],
+    seed: u64,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let mut budget = Budget::new(observer, OPERATION_BUDGET);
+    let result = bogo_sort(array, &mut StdRng::seed_from_u64(seed), &mut budget);
+    budget.finish(array, result)
+}
+
+// Implements Bozosort, reporting every step to the observer
+// Like Bogosort, but each attempt only swaps two random elements.
+// Gives up when it runs out of its operation budget.
+// seed: Seeds the RNG of the swaps, so runs can be repeated
+pub fn bozo_sort_visualized(
+    array: &mut [u32],
+    seed: u64,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let mut budget = Budget::new(observer, OPERATION_BUDGET);
+    let result = bozo_sort(array, &mut StdRng::seed_from_u64(seed), &mut budget);
+    budget.finish(array, result)
+}
@@ -0,0 +1,74 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Number of buckets used to sort n elements (about the square root of n)
+pub fn bucket_count(n: usize) -> usize {
+    ((n as f64).sqrt().ceil() as usize).max(1)
+}
+
+// Implements Bucket Sort, reporting every step to the observer
+// The elements are distributed into buckets of equal value ranges (auxiliary buffers 0, 1, ...),
+// gathered back in bucket order, then every bucket is insertion sorted in place
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the reads, buckets, comparisons, writes and sorted positions as events
+pub fn bucket_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    let count = bucket_count(n);
+    let max = array.iter().copied().max().unwrap_or(0) as u64;
+    let mut buckets: Vec<Vec<u32>> = vec![Vec::new(); count];
+    for id in 0..count {
+        observer.on_event(array, SortEvent::AuxBuffer(id, 0))?;
+    }
+
+    // Distribute the elements by value range
+    for i in 0..n {
+        observer.on_event(array, SortEvent::Read(i))?;
+        let id = (array[i] as u64 * count as u64 / (max + 1)) as usize;
+        buckets[id].push(array[i]);
+        let slot = buckets[id].len() - 1;
+        observer.on_event(array, SortEvent::AuxWrite(id, slot, array[i]))?;
+    }
+
+    // Gather the buckets back into the array in order, emptying them
+    let mut k = 0;
+    for (id, bucket) in buckets.iter().enumerate() {
+        for (slot, &value) in bucket.iter().enumerate() {
+            array[k] = value;
+            observer.on_event(array, SortEvent::Write(k, value))?;
+            observer.on_event(array, SortEvent::AuxRead(id, slot))?;
+            k += 1;
+        }
+    }
+
+    // Insertion sort every bucket, the elements never leave the range of their bucket
+    let mut start = 0;
+    for bucket in &buckets {
+        let end = start + bucket.len();
+        for i in start + 1..end {
+            let key = array[i];
+            let mut j = i;
+            while j > start {
+                observer.on_event(array, SortEvent::Compare(j - 1, j))?;
+                if array[j - 1] <= key {
+                    break;
+                }
+                array[j] = array[j - 1]; // Shift element to the right
+                observer.on_event(array, SortEvent::Write(j, array[j]))?;
+                j -= 1;
+            }
+            if j != i {
+                array[j] = key;
+                observer.on_event(array, SortEvent::Write(j, key))?;
+            }
+        }
+        // The bucket is sorted and every smaller element is in an earlier bucket
+        for i in start..end {
+            observer.on_event(array, SortEvent::MarkSorted(i))?;
+        }
+        start = end;
+    }
+    observer.on_event(array, SortEvent::Done)
+}
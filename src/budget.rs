@@ -0,0 +1,59 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Operations (comparisons, swaps, reads and writes) a hopeless algorithm may spend on a sort
+pub const OPERATION_BUDGET: u64 = 1_000_000;
+
+// Reported when an algorithm gave up because its budget was used up
+pub const BUDGET_EXHAUSTED: &str = "Operation budget exhausted";
+
+// Passes the events of an algorithm on to an observer until the algorithm has spent its budget,
+// then aborts the sort. Keeps algorithms that may never finish (e.g. Bogosort) from hanging.
+pub struct Budget<'a> {
+    observer: &'a mut dyn SortObserver,
+    remaining: u64,
+    exhausted: bool, // The budget aborted the sort, not the observer
+}
+
+impl<'a> Budget<'a> {
+    pub fn new(observer: &'a mut dyn SortObserver, operations: u64) -> Self {
+        Budget {
+            observer,
+            remaining: operations,
+            exhausted: false,
+        }
+    }
+
+    // Ends the sort with its result: a finished sort is done, a sort aborted by the budget
+    // reports that it gave up (see SortStats::gave_up) and ends without being done,
+    // a sort aborted by the observer stays aborted
+    pub fn finish(self, array: &[u32], result: ControlFlow<()>) -> ControlFlow<()> {
+        match result {
+            ControlFlow::Continue(()) => self.observer.on_event(array, SortEvent::Done),
+            ControlFlow::Break(()) if self.exhausted => self
+                .observer
+                .on_event(array, SortEvent::Phase(BUDGET_EXHAUSTED)),
+            ControlFlow::Break(()) => ControlFlow::Break(()),
+        }
+    }
+}
+
+impl SortObserver for Budget<'_> {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        // The algorithm already performed the operation, so the observer always gets to see it
+        self.observer.on_event(array, event)?;
+        if let SortEvent::Compare(..)
+        | SortEvent::AuxCompare(..)
+        | SortEvent::Swap(..)
+        | SortEvent::Read(_)
+        | SortEvent::Write(..) = event
+        {
+            self.remaining = self.remaining.saturating_sub(1);
+            if self.remaining == 0 {
+                self.exhausted = true;
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
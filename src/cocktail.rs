@@ -0,0 +1,51 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Implements Cocktail Shaker Sort, reporting every step to the observer
+// Bubble Sort passes alternate between both directions, so the largest remaining element
+// bubbles up to the end and the smallest one sinks to the start; stops once a pass swaps nothing
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the comparisons, swaps and sorted positions as events
+pub fn cocktail_shaker_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let mut start = 0; // First index of the unsorted portion
+    let mut end = array.len(); // One past the last index of the unsorted portion
+    while start + 1 < end {
+        // Forward pass
+        let mut swapped = false;
+        for j in start..end - 1 {
+            observer.on_event(array, SortEvent::Compare(j, j + 1))?;
+            if array[j] > array[j + 1] {
+                array.swap(j, j + 1);
+                observer.on_event(array, SortEvent::Swap(j, j + 1))?;
+                swapped = true;
+            }
+        }
+        // The largest remaining element has bubbled up to the end of the unsorted portion
+        end -= 1;
+        observer.on_event(array, SortEvent::MarkSorted(end))?;
+        if !swapped {
+            break;
+        }
+
+        // Backward pass
+        swapped = false;
+        for j in (start..end - 1).rev() {
+            observer.on_event(array, SortEvent::Compare(j, j + 1))?;
+            if array[j] > array[j + 1] {
+                array.swap(j, j + 1);
+                observer.on_event(array, SortEvent::Swap(j, j + 1))?;
+                swapped = true;
+            }
+        }
+        // The smallest remaining element has sunk to the start of the unsorted portion
+        observer.on_event(array, SortEvent::MarkSorted(start))?;
+        start += 1;
+        if !swapped {
+            break;
+        }
+    }
+    observer.on_event(array, SortEvent::Done)
+}
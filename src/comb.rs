@@ -0,0 +1,41 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// The shrink factor found best by Lacey and Box (1991)
+pub const DEFAULT_SHRINK: f64 = 1.3;
+
+// Implements Comb Sort, reporting every step to the observer
+// Bubble Sort over elements a gap apart, the gap shrinking by a constant factor every pass,
+// so small elements near the end (turtles) move forward quickly; ends with gap 1 passes
+// array: The array of numbers to be sorted (mutable)
+// shrink: The factor the gap is divided by after every pass (greater than 1)
+// observer: Receives the gaps, comparisons and swaps as events
+// Panics if the shrink factor is not greater than 1, the gap would never reach 1
+pub fn comb_sort_visualized(
+    array: &mut [u32],
+    shrink: f64,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    assert!(
+        shrink > 1.0,
+        "Comb Sort needs a shrink factor greater than 1"
+    );
+    let n = array.len();
+    let mut gap = n;
+    let mut sorted = n < 2;
+    while !sorted {
+        gap = ((gap as f64 / shrink) as usize).max(1);
+        // A pass with gap 1 that swaps nothing ends the sort
+        sorted = gap == 1;
+        observer.on_event(array, SortEvent::Gap(gap))?;
+        for i in 0..n - gap {
+            observer.on_event(array, SortEvent::Compare(i, i + gap))?;
+            if array[i] > array[i + gap] {
+                array.swap(i, i + gap);
+                observer.on_event(array, SortEvent::Swap(i, i + gap))?;
+                sorted = false;
+            }
+        }
+    }
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,121 @@
+use crate::draw::darken_rect;
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::render::SortView;
+use crate::WIDTH;
+use std::ops::ControlFlow;
+
+const LABEL_SCALE: usize = 3;
+const LABEL_MARGIN: usize = 12;
+const LINE_HEIGHT: usize = (GLYPH_HEIGHT + 3) * LABEL_SCALE;
+const COUNTER_COLOR: u32 = 0xFFFF00;
+const TEXT_COLOR: u32 = 0xFFFFFF;
+
+// The operation an algorithm is measured by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cost {
+    Writes, // Elements written into the array (Cycle Sort)
+    Flips,  // Prefix reversals (Pancake Sort)
+}
+
+// Counts the operation that matters for an algorithm whose cost is not its comparisons,
+// next to the bound the algorithm is known for
+pub struct CostView {
+    cost: Cost,
+    count: usize,
+    bound: Option<usize>,
+}
+
+impl CostView {
+    // Counts the writes, against the number of elements out of place at the start:
+    // no sort can get by with fewer writes
+    pub fn writes() -> Self {
+        CostView {
+            cost: Cost::Writes,
+            count: 0,
+            bound: None, // Known with the first event
+        }
+    }
+
+    // Counts the flips, against the 2n - 3 flips Pancake Sort needs at most
+    // num_bars: The number of elements being sorted
+    pub fn flips(num_bars: usize) -> Self {
+        CostView {
+            cost: Cost::Flips,
+            count: 0,
+            bound: Some((2 * num_bars).saturating_sub(3)),
+        }
+    }
+
+    // How often the operation was performed
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    // The bound the count is measured against
+    pub fn bound(&self) -> Option<usize> {
+        self.bound
+    }
+
+    fn lines(&self) -> [String; 2] {
+        let bound = self.bound.unwrap_or(0);
+        match self.cost {
+            Cost::Writes => [
+                format!("Writes: {}", self.count),
+                format!("Misplaced at start: {}", bound),
+            ],
+            Cost::Flips => [
+                format!("Flips: {}", self.count),
+                format!("At most 2n - 3: {}", bound),
+            ],
+        }
+    }
+}
+
+impl SortObserver for CostView {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        if self.bound.is_none() {
+            // No event of the sort has moved an element yet
+            let mut sorted = array.to_vec();
+            sorted.sort_unstable();
+            let misplaced = array.iter().zip(&sorted).filter(|(a, b)| a != b).count();
+            self.bound = Some(misplaced);
+        }
+        match (self.cost, event) {
+            (Cost::Writes, SortEvent::Write(..)) => self.count += 1,
+            (Cost::Writes, SortEvent::Swap(..)) => self.count += 2,
+            (Cost::Flips, SortEvent::Flip(_)) => self.count += 1,
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for CostView {
+    // Draws the counter into the top right corner of the window
+    fn draw(&self, buffer: &mut [u32]) {
+        let lines = self.lines();
+        let width = lines
+            .iter()
+            .map(|line| text_width(line, LABEL_SCALE))
+            .max()
+            .unwrap_or(0);
+        let x = WIDTH - width - 2 * LABEL_MARGIN;
+        darken_rect(
+            buffer,
+            x - LABEL_MARGIN,
+            LABEL_MARGIN / 2,
+            width + 2 * LABEL_MARGIN,
+            lines.len() * LINE_HEIGHT + LABEL_MARGIN,
+        );
+        for (index, line) in lines.iter().enumerate() {
+            let color = if index == 0 {
+                COUNTER_COLOR
+            } else {
+                TEXT_COLOR
+            };
+            let y = LABEL_MARGIN * 3 / 2 + index * LINE_HEIGHT;
+            draw_text(buffer, x, y, line, LABEL_SCALE, color);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Id of the auxiliary buffer holding the number of elements per value
+pub const COUNTS: usize = 0;
+
+// Implements Counting Sort, reporting every step to the observer
+// The elements are counted per value into a histogram, then the array is rebuilt from it
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the reads, counts, writes and sorted positions as events
+pub fn counting_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    let max = array.iter().copied().max().unwrap_or(0) as usize;
+    let mut counts: Vec<u32> = vec![0; max + 1];
+    observer.on_event(array, SortEvent::AuxBuffer(COUNTS, max + 1))?;
+
+    // Count how often every value occurs
+    for i in 0..n {
+        observer.on_event(array, SortEvent::Read(i))?;
+        let value = array[i] as usize;
+        counts[value] += 1;
+        observer.on_event(array, SortEvent::AuxWrite(COUNTS, value, counts[value]))?;
+    }
+
+    // Rebuild the array from the smallest value up, draining the histogram
+    let mut k = 0;
+    for (value, count) in counts.iter_mut().enumerate() {
+        while *count > 0 {
+            array[k] = value as u32;
+            observer.on_event(array, SortEvent::Write(k, array[k]))?;
+            *count -= 1;
+            observer.on_event(array, SortEvent::AuxWrite(COUNTS, value, *count))?;
+            observer.on_event(array, SortEvent::MarkSorted(k))?;
+            k += 1;
+        }
+    }
+    observer.on_event(array, SortEvent::Done)
+}
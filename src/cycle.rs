@@ -0,0 +1,47 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Implements Cycle Sort, reporting every step to the observer
+// Follows the cycles of the permutation that sorts the array: the element taken out of a
+// position is written straight to its final position, whose element is taken out next,
+// until the cycle closes. Every element is written at most once, the fewest writes possible.
+pub fn cycle_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    for cycle_start in 0..n.saturating_sub(1) {
+        // The element taken out of the array; cycle_start keeps a stale copy until the cycle closes
+        let mut item = array[cycle_start];
+        observer.on_event(array, SortEvent::Read(cycle_start))?;
+        loop {
+            // The final position of item: after every smaller element and the equal ones placed before
+            let mut pos = cycle_start;
+            for i in cycle_start + 1..n {
+                observer.on_event(array, SortEvent::Compare(i, cycle_start))?;
+                if array[i] < item {
+                    pos += 1;
+                }
+            }
+            if pos == cycle_start {
+                break;
+            }
+            while array[pos] == item {
+                pos += 1;
+            }
+            // Write item to its position and take out the element that was there
+            let taken = array[pos];
+            array[pos] = item;
+            observer.on_event(array, SortEvent::Write(pos, item))?;
+            observer.on_event(array, SortEvent::MarkSorted(pos))?;
+            item = taken;
+        }
+        // The cycle closed: the last element taken out belongs where it started
+        if item != array[cycle_start] {
+            array[cycle_start] = item;
+            observer.on_event(array, SortEvent::Write(cycle_start, item))?;
+        }
+        observer.on_event(array, SortEvent::MarkSorted(cycle_start))?;
+    }
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,191 @@
+use crate::draw::fill_rect;
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, GLYPH_HEIGHT};
+use crate::network::Network;
+use crate::render::SortView;
+use crate::{HEIGHT, WIDTH};
+use std::ops::ControlFlow;
+
+// Height of the panel below the bars
+const PANEL_HEIGHT: usize = 360;
+const PANEL_MARGIN: usize = 16; // Kept free at the bottom for the timeline
+const LABEL_SCALE: usize = 2;
+const LABEL_HEIGHT: usize = (GLYPH_HEIGHT + 3) * LABEL_SCALE;
+const DOT_SIZE: usize = 4;
+
+const SEPARATOR_COLOR: u32 = 0x404040;
+const WIRE_COLOR: u32 = 0x404040;
+const PENDING_COLOR: u32 = 0x606060; // Comparators of the layers still to come
+const DONE_COLOR: u32 = 0xFFFFFF; // Comparators of the layers already executed
+const LAYER_COLOR: u32 = 0xFFFF00; // Comparators of the executing layer
+const LAYER_BACKGROUND: u32 = 0x303000;
+const ACTIVE_COLOR: u32 = 0xFF0000; // The comparator of the executing layer compared last
+const LABEL_COLOR: u32 = 0xFFFFFF;
+
+// Draws a sorting network as the classic comparator diagram beneath the bars: one horizontal
+// wire per element and a vertical comparator for every compare-and-swap, layer by layer from
+// left to right, with the executing layer highlighted
+pub struct NetworkView {
+    num_wires: usize,
+    // The comparators of every layer with the column they are drawn in within their layer,
+    // so comparators spanning the same wires do not overlap
+    layers: Vec<Vec<(usize, usize, usize)>>,
+    columns: Vec<usize>, // Number of columns of every layer
+    current: Option<usize>,
+    active: Option<(usize, usize)>,
+    done: bool,
+}
+
+impl NetworkView {
+    // num_bars: The number of elements being sorted, one wire each
+    // network: The comparators the algorithm executes
+    pub fn new(num_bars: usize, network: Network) -> Self {
+        let mut layers = Vec::new();
+        let mut columns = Vec::new();
+        for layer in network {
+            // Put every comparator into the first column it does not overlap in
+            let mut ends: Vec<usize> = Vec::new(); // Lowest free wire of every column
+            let mut placed = Vec::new();
+            for (i, j) in layer {
+                let column = match ends.iter().position(|&end| end <= i) {
+                    Some(column) => column,
+                    None => {
+                        ends.push(0);
+                        ends.len() - 1
+                    }
+                };
+                ends[column] = j + 1;
+                placed.push((i, j, column));
+            }
+            columns.push(ends.len());
+            layers.push(placed);
+        }
+        NetworkView {
+            num_wires: num_bars,
+            layers,
+            columns,
+            current: None,
+            active: None,
+            done: false,
+        }
+    }
+
+    // The layer the algorithm is executing
+    pub fn current_layer(&self) -> Option<usize> {
+        self.current
+    }
+
+    // Color of the comparators of a layer
+    fn layer_color(&self, index: usize) -> u32 {
+        match self.current {
+            _ if self.done => DONE_COLOR,
+            Some(current) if index == current => LAYER_COLOR,
+            Some(current) if index < current => DONE_COLOR,
+            _ => PENDING_COLOR,
+        }
+    }
+}
+
+impl SortObserver for NetworkView {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        match event {
+            SortEvent::Layer(index) => {
+                self.current = Some(index);
+                self.active = None;
+            }
+            SortEvent::Compare(i, j) => self.active = Some((i, j)),
+            SortEvent::Done => {
+                self.current = None;
+                self.active = None;
+                self.done = true;
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for NetworkView {
+    fn draw(&self, buffer: &mut [u32]) {
+        let top = HEIGHT - PANEL_HEIGHT;
+        fill_rect(buffer, 0, top, WIDTH, 2, SEPARATOR_COLOR);
+        let label = match self.current {
+            Some(current) => format!(
+                "Layer {} of {}   Comparators: {}",
+                current + 1,
+                self.layers.len(),
+                self.layers[current].len()
+            ),
+            None => format!("Layers: {}", self.layers.len()),
+        };
+        draw_text(
+            buffer,
+            PANEL_MARGIN,
+            top + PANEL_MARGIN / 2,
+            &label,
+            LABEL_SCALE,
+            LABEL_COLOR,
+        );
+
+        // The wires, spread over the rest of the panel
+        let wires_top = top + PANEL_MARGIN + LABEL_HEIGHT;
+        let wires_height = HEIGHT - PANEL_MARGIN - wires_top;
+        let wire_y =
+            |i: usize| wires_top + i * wires_height / self.num_wires.saturating_sub(1).max(1);
+        for i in 0..self.num_wires {
+            fill_rect(buffer, 0, wire_y(i), WIDTH, 1, WIRE_COLOR);
+        }
+
+        // Every layer gets a share of the width by its number of columns, plus one column of space
+        let total: usize = self.columns.iter().map(|columns| columns + 1).sum();
+        let column_x =
+            |column: usize| PANEL_MARGIN + column * (WIDTH - 2 * PANEL_MARGIN) / total.max(1);
+        let mut first_column = 0;
+        for (index, layer) in self.layers.iter().enumerate() {
+            if self.current == Some(index) {
+                let x = column_x(first_column);
+                let width = column_x(first_column + self.columns[index] + 1) - x;
+                fill_rect(
+                    buffer,
+                    x,
+                    wires_top,
+                    width,
+                    wires_height + 1,
+                    LAYER_BACKGROUND,
+                );
+                // Keep the wires visible in front of the background
+                for i in 0..self.num_wires {
+                    fill_rect(buffer, x, wire_y(i), width, 1, WIRE_COLOR);
+                }
+            }
+            let color = self.layer_color(index);
+            for &(i, j, column) in layer {
+                let color = if self.current == Some(index) && self.active == Some((i, j)) {
+                    ACTIVE_COLOR
+                } else {
+                    color
+                };
+                // Centered in its column, the spacing column comes first
+                let x =
+                    (column_x(first_column + column + 1) + column_x(first_column + column + 2)) / 2;
+                let (y1, y2) = (wire_y(i), wire_y(j));
+                fill_rect(buffer, x, y1, 1, y2 - y1 + 1, color);
+                for y in [y1, y2] {
+                    fill_rect(
+                        buffer,
+                        x.saturating_sub(DOT_SIZE / 2),
+                        y.saturating_sub(DOT_SIZE / 2),
+                        DOT_SIZE,
+                        DOT_SIZE,
+                        color,
+                    );
+                }
+            }
+            first_column += self.columns[index] + 1;
+        }
+    }
+
+    fn panel_height(&self) -> usize {
+        PANEL_HEIGHT
+    }
+}
@@ -0,0 +1,357 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::history::History;
+use crate::overlay::StatsOverlay;
+use crate::render::BarRenderer;
+use crate::{Algorithm, HEIGHT, WIDTH};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window};
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+// Frames are presented at most this often, operations in between are batched into one frame
+const FRAME_INTERVAL: Duration = Duration::from_micros(16_600); // ~60 FPS
+
+// Limits of the playback speed, in operations per second
+pub const MIN_OPS_PER_SECOND: f64 = 1.0;
+pub const MAX_OPS_PER_SECOND: f64 = 10_000_000.0;
+
+// If the sort falls this far behind its schedule (e.g. the renderer cannot keep up),
+// the schedule restarts instead of rushing through the backlog
+const MAX_LAG: Duration = Duration::from_millis(250);
+
+// What to do after an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pace {
+    pub present: bool,  // Show the operation in a new frame
+    pub wait: Duration, // Time to wait before the next operation
+}
+
+// Runs the operations of a sort at a fixed rate, independent of the frame rate.
+// Slow speeds show every operation in its own frame, fast speeds batch many operations per frame.
+pub struct Pacer {
+    ops_per_second: f64,
+    epoch: Instant,              // When the current schedule started
+    ops: u64,                    // Operations since the epoch
+    last_frame: Option<Instant>, // When the last frame was presented
+}
+
+impl Pacer {
+    // ops_per_second: The playback speed (clamped to MIN/MAX_OPS_PER_SECOND)
+    pub fn new(ops_per_second: f64) -> Self {
+        Pacer {
+            ops_per_second: ops_per_second.clamp(MIN_OPS_PER_SECOND, MAX_OPS_PER_SECOND),
+            epoch: Instant::now(),
+            ops: 0,
+            last_frame: None,
+        }
+    }
+
+    pub fn ops_per_second(&self) -> f64 {
+        self.ops_per_second
+    }
+
+    // Changes the playback speed, starting a new schedule
+    pub fn set_ops_per_second(&mut self, ops_per_second: f64) {
+        self.ops_per_second = ops_per_second.clamp(MIN_OPS_PER_SECOND, MAX_OPS_PER_SECOND);
+        self.restart();
+    }
+
+    // Starts a new schedule from now (e.g. after a pause)
+    pub fn restart(&mut self) {
+        self.epoch = Instant::now();
+        self.ops = 0;
+    }
+
+    // Called after every operation
+    // now: The current time
+    pub fn step(&mut self, now: Instant) -> Pace {
+        self.ops += 1;
+        let due = self.epoch + Duration::from_secs_f64(self.ops as f64 / self.ops_per_second);
+        // Ahead of schedule the sort waits for the time of the operation,
+        // behind schedule it keeps going without waiting
+        let wait = due.saturating_duration_since(now);
+        if now.saturating_duration_since(due) > MAX_LAG {
+            self.epoch = now;
+            self.ops = 0;
+        }
+        // A frame is presented at most once per frame interval, unless the wait is long anyway
+        let present = wait >= FRAME_INTERVAL
+            || self
+                .last_frame
+                .is_none_or(|last| now - last >= FRAME_INTERVAL);
+        if present {
+            self.last_frame = Some(now);
+        }
+        Pace { present, wait }
+    }
+}
+
+// The timeline at the bottom of the window shows the position in the recorded sort
+const TIMELINE_HEIGHT: usize = 8;
+const TIMELINE_HIT_HEIGHT: usize = 32; // Height of the area that reacts to the mouse
+const TIMELINE_BACKGROUND: u32 = 0x303030;
+const TIMELINE_PROGRESS: u32 = 0x909090;
+const TIMELINE_CURSOR: u32 = 0xFFFFFF;
+
+// Draws the timeline over the bottom of the pixel buffer
+// position: The operation being shown
+// total: The number of recorded operations
+pub fn draw_timeline(buffer: &mut [u32], position: usize, total: usize) {
+    let cursor = if total > 1 {
+        position * (WIDTH - 1) / (total - 1)
+    } else {
+        WIDTH - 1
+    };
+    for y in (HEIGHT - TIMELINE_HEIGHT)..HEIGHT {
+        let row = &mut buffer[y * WIDTH..(y + 1) * WIDTH];
+        row[..cursor].fill(TIMELINE_PROGRESS);
+        row[cursor..].fill(TIMELINE_BACKGROUND);
+        row[cursor.saturating_sub(1)..(cursor + 2).min(WIDTH)].fill(TIMELINE_CURSOR);
+    }
+}
+
+// Observer that presents the frames of a sort in a minifb window at a configurable speed
+// While the sort runs: +/- double or halve the speed, Space pauses and resumes,
+// Right advances one operation while paused and ESC (or closing the window) aborts the sort.
+// Every operation is recorded: Left steps backwards and clicking or dragging on the timeline
+// scrubs through the sort (pausing it); Right steps forward again up to the live sort.
+pub struct WindowDisplay<'a> {
+    window: &'a mut Window,
+    renderer: BarRenderer,
+    pacer: Pacer,
+    paused: bool,
+    interrupted: bool, // The speed or the pause state changed while waiting
+    history: History,
+    review: Option<usize>, // The recorded operation being shown, None while showing the live sort
+    overlay: StatsOverlay,
+    frame: Vec<u32>, // The presented frame: the bars plus the timeline and the overlay
+}
+
+impl<'a> WindowDisplay<'a> {
+    // window: The minifb window to update
+    // algorithm: The algorithm being shown (its name and overlay)
+    // array: The array before the sort starts
+    // bar_width: The calculated width of each bar
+    // ops_per_second: The initial playback speed
+    pub fn new(
+        window: &'a mut Window,
+        algorithm: &Algorithm,
+        array: &[u32],
+        bar_width: usize,
+        ops_per_second: f64,
+    ) -> Self {
+        let mut renderer = BarRenderer::new(array.len(), bar_width);
+        if let Some(view) = algorithm.view {
+            renderer.set_view(view(array.len()));
+        }
+        let mut display = WindowDisplay {
+            window,
+            renderer,
+            pacer: Pacer::new(ops_per_second),
+            paused: false,
+            interrupted: false,
+            history: History::new(array),
+            review: None,
+            overlay: StatsOverlay::new(algorithm.name, array.len()),
+            frame: vec![0; WIDTH * HEIGHT],
+        };
+        display.update_title();
+        display
+    }
+
+    // Draws the array as it is (e.g. the unsorted array before the sort starts)
+    pub fn show(&mut self, array: &[u32]) {
+        self.renderer.draw(array);
+        self.present();
+    }
+
+    // Keeps the window open and responsive until ESC is pressed or it is closed
+    // The finished sort can still be reviewed with Left/Right and the timeline
+    // array: The sorted array
+    pub fn wait_for_exit(&mut self, array: &[u32]) {
+        while self.window.is_open() && !self.window.is_key_down(Key::Escape) {
+            self.present();
+            self.handle_review_input(array);
+        }
+    }
+
+    fn present(&mut self) {
+        // The renderer keeps the bars only, so presenting again never stacks the overlays
+        self.frame.copy_from_slice(&self.renderer.buffer);
+        if !self.history.is_empty() {
+            let live = self.history.len() - 1;
+            draw_timeline(
+                &mut self.frame,
+                self.review.unwrap_or(live),
+                self.history.len(),
+            );
+        }
+        self.overlay.draw(&mut self.frame);
+        self.window
+            .update_with_buffer(&self.frame, WIDTH, HEIGHT)
+            .unwrap();
+    }
+
+    // Waits before the next operation, keeping the window responsive
+    // array: The live array
+    fn wait(&mut self, array: &[u32], duration: Duration) -> ControlFlow<()> {
+        let until = Instant::now() + duration;
+        self.interrupted = false;
+        loop {
+            self.handle_keys(array)?;
+            self.handle_review_input(array);
+            if self.paused {
+                return self.wait_while_paused(array);
+            }
+            if self.interrupted {
+                // A new speed starts a new schedule
+                return ControlFlow::Continue(());
+            }
+            match until.checked_duration_since(Instant::now()) {
+                Some(remaining) if !remaining.is_zero() => {
+                    std::thread::sleep(remaining.min(FRAME_INTERVAL));
+                    self.window.update();
+                }
+                _ => return ControlFlow::Continue(()),
+            }
+        }
+    }
+
+    // Holds the sort until it is resumed or a single step is requested
+    // array: The live array
+    fn wait_while_paused(&mut self, array: &[u32]) -> ControlFlow<()> {
+        // The time shown by the overlay stands still until the sort continues
+        self.overlay.pause();
+        loop {
+            self.present();
+            self.handle_keys(array)?;
+            if !self.paused {
+                if self.review.take().is_some() {
+                    // Resuming always continues the live sort
+                    self.renderer.redraw(array);
+                }
+                // Resume on a fresh schedule instead of catching up on the pause
+                self.pacer.restart();
+                self.overlay.resume();
+                return ControlFlow::Continue(());
+            }
+            if self.handle_review_input(array) {
+                self.overlay.resume();
+                return ControlFlow::Continue(());
+            }
+        }
+    }
+
+    // Handles the playback keys, breaks if the sort should be aborted
+    // array: The live array, redrawn when the view is toggled
+    fn handle_keys(&mut self, array: &[u32]) -> ControlFlow<()> {
+        if !self.window.is_open() || self.window.is_key_down(Key::Escape) {
+            return ControlFlow::Break(());
+        }
+        let pressed = |key| self.window.is_key_pressed(key, KeyRepeat::Yes);
+        let factor = if pressed(Key::Equal) || pressed(Key::NumPadPlus) {
+            Some(2.0)
+        } else if pressed(Key::Minus) || pressed(Key::NumPadMinus) {
+            Some(0.5)
+        } else {
+            None
+        };
+        if let Some(factor) = factor {
+            self.pacer
+                .set_ops_per_second(self.pacer.ops_per_second() * factor);
+            self.interrupted = true;
+            self.update_title();
+        }
+        if self.window.is_key_pressed(Key::T, KeyRepeat::No) {
+            self.renderer.toggle_view();
+            if self.review.is_none() {
+                self.renderer.redraw(array);
+            }
+        }
+        if self.window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            self.paused = !self.paused;
+            self.interrupted = true;
+            self.update_title();
+        }
+        ControlFlow::Continue(())
+    }
+
+    // Handles Left/Right and the timeline to move through the recorded sort
+    // live: The live array, shown when the review reaches the end of the history
+    // Returns true when Right was pressed at the live end while paused (step the sort)
+    fn handle_review_input(&mut self, live: &[u32]) -> bool {
+        if self.history.is_empty() {
+            return false;
+        }
+        let last = self.history.len() - 1;
+        let current = self.review.unwrap_or(last);
+        let target = if self.window.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+            current.saturating_sub(1)
+        } else if self.window.is_key_pressed(Key::Right, KeyRepeat::Yes) {
+            if self.review.is_none() {
+                return self.paused;
+            }
+            current + 1
+        } else if let Some(x) = self.timeline_click() {
+            x * last / (WIDTH - 1)
+        } else {
+            return false;
+        };
+        if target == current {
+            return false;
+        }
+        if target >= last {
+            self.review = None;
+            self.renderer.redraw(live);
+        } else {
+            // Looking back pauses the sort
+            self.review = Some(target);
+            self.renderer.draw_state(&self.history.state_at(target));
+            if !self.paused {
+                self.paused = true;
+                self.interrupted = true;
+                self.update_title();
+            }
+        }
+        false
+    }
+
+    // The x coordinate of the mouse while it is held down on the timeline
+    fn timeline_click(&self) -> Option<usize> {
+        if !self.window.get_mouse_down(MouseButton::Left) {
+            return None;
+        }
+        let (x, y) = self.window.get_mouse_pos(MouseMode::Clamp)?;
+        if (y as usize) < HEIGHT - TIMELINE_HIT_HEIGHT {
+            return None;
+        }
+        Some((x as usize).min(WIDTH - 1))
+    }
+
+    fn update_title(&mut self) {
+        self.window.set_title(&format!(
+            "Sorting Algorithm Visualizer{} - {} operations/s - Space: pause, Left/Right: step, +/-: speed, ESC: exit",
+            if self.paused { " (paused)" } else { "" },
+            self.pacer.ops_per_second()
+        ));
+    }
+}
+
+impl SortObserver for WindowDisplay<'_> {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        self.history.record(array, event);
+        self.overlay.on_event(array, event)?;
+        if !self.renderer.update(array, event) {
+            return ControlFlow::Continue(());
+        }
+        let pace = self.pacer.step(Instant::now());
+        // While paused every single step is shown
+        if pace.present || self.paused || event == SortEvent::Done {
+            self.renderer.redraw(array);
+            self.present();
+        }
+        if event == SortEvent::Done {
+            return ControlFlow::Continue(());
+        }
+        self.wait(array, pace.wait)
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{HEIGHT, WIDTH};
+
+// Basic shapes for overlays drawn on top of the bars, all clipped to the window
+
+// Fills a rectangle with a color
+pub fn fill_rect(buffer: &mut [u32], x: usize, y: usize, width: usize, height: usize, color: u32) {
+    for row in y.min(HEIGHT)..(y + height).min(HEIGHT) {
+        buffer[row * WIDTH + x.min(WIDTH)..row * WIDTH + (x + width).min(WIDTH)].fill(color);
+    }
+}
+
+// Darkens a rectangle to a quarter of its brightness, keeping text over it readable
+pub fn darken_rect(buffer: &mut [u32], x: usize, y: usize, width: usize, height: usize) {
+    for row in y.min(HEIGHT)..(y + height).min(HEIGHT) {
+        for pixel in &mut buffer[row * WIDTH + x.min(WIDTH)..row * WIDTH + (x + width).min(WIDTH)] {
+            *pixel = (*pixel >> 2) & 0x3F3F3F;
+        }
+    }
+}
+
+// Draws the outline of a rectangle
+pub fn draw_rect(buffer: &mut [u32], x: usize, y: usize, width: usize, height: usize, color: u32) {
+    fill_rect(buffer, x, y, width, 2, color);
+    fill_rect(buffer, x, (y + height).saturating_sub(2), width, 2, color);
+    fill_rect(buffer, x, y, 2, height, color);
+    fill_rect(buffer, (x + width).saturating_sub(2), y, 2, height, color);
+}
+
+// Draws a line between two points (Bresenham)
+pub fn draw_line(buffer: &mut [u32], from: (usize, usize), to: (usize, usize), color: u32) {
+    let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+    let (x1, y1) = (to.0 as i64, to.1 as i64);
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let sx = if x < x1 { 1 } else { -1 };
+    let sy = if y < y1 { 1 } else { -1 };
+    let mut error = dx + dy;
+    loop {
+        if (0..WIDTH as i64).contains(&x) && (0..HEIGHT as i64).contains(&y) {
+            buffer[y as usize * WIDTH + x as usize] = color;
+        }
+        if x == x1 && y == y1 {
+            break;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+}
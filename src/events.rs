@@ -0,0 +1,135 @@
+use std::ops::ControlFlow;
+
+// Events emitted by the sorting algorithms while they run.
+// The algorithms only mutate the array and report what they did; rendering,
+// audio and any other consumer subscribe to this stream through a SortObserver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortEvent {
+    // The elements at the two indices are being compared
+    Compare(usize, usize),
+    // The elements at the two indices have been swapped
+    Swap(usize, usize),
+    // The value has been written to the index (the array already holds it)
+    Write(usize, u32),
+    // The element at the index has been read without comparing it (e.g. to find its digit)
+    Read(usize),
+    // An auxiliary buffer (outside the array) with the id and length was allocated or cleared
+    AuxBuffer(usize, usize),
+    // The value has been written to the index of the auxiliary buffer with the id
+    // (writing past the end of the buffer grows it)
+    AuxWrite(usize, usize, u32),
+    // The element at the index of the auxiliary buffer with the id has been taken out (read)
+    AuxRead(usize, usize),
+    // The value at the index of the auxiliary buffer with the id is being compared with the
+    // element at the index of the array (the value is given as observers cannot see the buffer)
+    AuxCompare(usize, usize, u32, usize),
+    // The algorithm starts a pass over the elements that are the given distance apart
+    Gap(usize),
+    // The element at the index has reached its final sorted position
+    MarkSorted(usize),
+    // The element at the index is the pivot of the current partition
+    Pivot(usize),
+    // The elements from the index on, with the length, form a sorted run (e.g. of TimSort)
+    // A run replaces the runs it overlaps, so merged runs are reported as one new run
+    Run(usize, usize),
+    // The algorithm switches to another strategy (e.g. Introsort falling back to Heap Sort)
+    Phase(&'static str),
+    // The elements from the index on, with the length, serve as an internal buffer
+    // (e.g. the keys of Block Sort); a length of 0 gives the buffer back
+    Buffer(usize, usize),
+    // The algorithm starts the layer of its sorting network with the index
+    Layer(usize),
+    // The algorithm starts reversing the prefix of the array with the length (e.g. Pancake Sort)
+    Flip(usize),
+    // The elements from the index on, with the length, form a heap (e.g. a Leonardo heap of
+    // Smoothsort), its root being the last of them. A tree replaces the trees it overlaps.
+    Tree(usize, usize),
+    // The algorithm has finished and the whole array is sorted
+    Done,
+}
+
+// Receives every event emitted by a sorting algorithm
+// array: The state of the array right after the event happened
+// event: The operation that was performed
+// Returning ControlFlow::Break aborts the sort; the algorithms stop right away (with `?`)
+// and the array is left as it is.
+pub trait SortObserver {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()>;
+}
+
+// Observer that ignores every event (useful to run an algorithm on its own)
+pub struct NullObserver;
+
+impl SortObserver for NullObserver {
+    fn on_event(&mut self, _array: &[u32], _event: SortEvent) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+// A plain list of events records the whole run
+impl SortObserver for Vec<SortEvent> {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        self.push(event);
+        ControlFlow::Continue(())
+    }
+}
+
+impl<T: SortObserver + ?Sized> SortObserver for &mut T {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        (**self).on_event(array, event)
+    }
+}
+
+impl<T: SortObserver + ?Sized> SortObserver for Box<T> {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        (**self).on_event(array, event)
+    }
+}
+
+// An optional observer only receives events when it is present
+impl<T: SortObserver> SortObserver for Option<T> {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        match self {
+            Some(observer) => observer.on_event(array, event),
+            None => ControlFlow::Continue(()),
+        }
+    }
+}
+
+// A pair of observers forwards every event to both, in order.
+// Pairs can be nested to subscribe any number of consumers.
+// If the first one aborts the sort, the second one does not see the event.
+impl<A: SortObserver, B: SortObserver> SortObserver for (A, B) {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        self.0.on_event(array, event)?;
+        self.1.on_event(array, event)
+    }
+}
+
+// One event per line in traces, e.g. "compare 3 4" or "write 5 17"
+impl std::fmt::Display for SortEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortEvent::Compare(a, b) => write!(f, "compare {} {}", a, b),
+            SortEvent::Swap(a, b) => write!(f, "swap {} {}", a, b),
+            SortEvent::Write(i, value) => write!(f, "write {} {}", i, value),
+            SortEvent::Read(i) => write!(f, "read {}", i),
+            SortEvent::AuxBuffer(id, len) => write!(f, "aux-buffer {} {}", id, len),
+            SortEvent::AuxWrite(id, i, value) => write!(f, "aux-write {} {} {}", id, i, value),
+            SortEvent::AuxRead(id, i) => write!(f, "aux-read {} {}", id, i),
+            SortEvent::AuxCompare(id, i, value, index) => {
+                write!(f, "aux-compare {} {} {} {}", id, i, value, index)
+            }
+            SortEvent::Gap(gap) => write!(f, "gap {}", gap),
+            SortEvent::MarkSorted(i) => write!(f, "sorted {}", i),
+            SortEvent::Pivot(i) => write!(f, "pivot {}", i),
+            SortEvent::Run(start, len) => write!(f, "run {} {}", start, len),
+            SortEvent::Phase(name) => write!(f, "phase {}", name),
+            SortEvent::Buffer(start, len) => write!(f, "buffer {} {}", start, len),
+            SortEvent::Layer(index) => write!(f, "layer {}", index),
+            SortEvent::Flip(len) => write!(f, "flip {}", len),
+            SortEvent::Tree(start, len) => write!(f, "tree {} {}", start, len),
+            SortEvent::Done => write!(f, "done"),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::render::{BarRenderer, SortView};
+use crate::{HEIGHT, WIDTH};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+
+// Writes a frame from the pixel buffer as a binary PPM (P6) image
+// writer: Where the image is written
+// buffer: The pixel buffer (0xRRGGBB per pixel, WIDTH * HEIGHT pixels)
+pub fn write_ppm<W: Write>(writer: &mut W, buffer: &[u32]) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", WIDTH, HEIGHT)?;
+    let mut pixels = Vec::with_capacity(WIDTH * HEIGHT * 3);
+    for &pixel in buffer {
+        pixels.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+    }
+    writer.write_all(&pixels)
+}
+
+// The colors a GIF frame can use. The renderer draws with these colors, any other
+// color is mapped to the nearest one.
+const GIF_PALETTE: [u32; 16] = [
+    0x000000, // Background
+    0x0000FF, // Unsorted bars
+    0xFF0000, // Highlighted bars
+    0x00FF00, // Sorted bars
+    0xFFFFFF, 0xFFFF00, 0xFF00FF, 0x00FFFF,
+    // Views: panels, separators and the subsequence colors of Shell Sort
+    0x202020, 0x404040, 0x808080, 0xC0C0C0, 0x00A0FF, 0xA000FF, 0xFF8000, 0x00C080,
+];
+
+// Maps pixels to the index of the nearest palette color
+#[derive(Default)]
+struct PaletteQuantizer {
+    cache: HashMap<u32, u8>, // Colors that were already mapped
+}
+
+impl PaletteQuantizer {
+    fn index_of(&mut self, color: u32) -> u8 {
+        *self.cache.entry(color).or_insert_with(|| {
+            let channels = |c: u32| {
+                [
+                    (c >> 16) as i32 & 0xFF,
+                    (c >> 8) as i32 & 0xFF,
+                    c as i32 & 0xFF,
+                ]
+            };
+            let wanted = channels(color);
+            let distance = |candidate: &u32| -> i32 {
+                let c = channels(*candidate);
+                (0..3).map(|i| (c[i] - wanted[i]).pow(2)).sum()
+            };
+            let (index, _) = GIF_PALETTE
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, candidate)| distance(candidate))
+                .unwrap();
+            index as u8
+        })
+    }
+}
+
+// Settings for an animated GIF export
+// frame_step: Only every frame_step-th frame is kept (frame decimation); 1 keeps every frame
+// scale: The frames are shrunk by this factor (e.g. 4 gives 480x270 for a 1920x1080 window)
+// delay_cs: How long each frame is shown, in hundredths of a second
+#[derive(Debug, Clone, Copy)]
+pub struct GifOptions {
+    pub frame_step: usize,
+    pub scale: usize,
+    pub delay_cs: u16,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        GifOptions {
+            frame_step: 1,
+            scale: 4,
+            delay_cs: 2,
+        }
+    }
+}
+
+// How long the final sorted frame stays on screen before the animation loops
+const GIF_FINAL_DELAY_CS: u16 = 300;
+
+// Observer that renders the frames of a sort and writes them as an animated GIF
+// The first write error is kept and reported by finish(), later frames are dropped
+pub struct GifRecorder<W: Write> {
+    encoder: gif::Encoder<W>,
+    renderer: BarRenderer,
+    quantizer: PaletteQuantizer,
+    options: GifOptions,
+    frames: usize, // Number of frames rendered so far (kept or not)
+    error: Option<io::Error>,
+}
+
+impl<W: Write> GifRecorder<W> {
+    // writer: Where the GIF is written
+    // num_bars: The total number of bars
+    // bar_width: The calculated width of each bar
+    // options: Decimation, scale and timing of the animation
+    pub fn new(
+        writer: W,
+        num_bars: usize,
+        bar_width: usize,
+        options: GifOptions,
+    ) -> io::Result<Self> {
+        let options = GifOptions {
+            frame_step: options.frame_step.max(1),
+            scale: options.scale.max(1),
+            ..options
+        };
+        let palette: Vec<u8> = GIF_PALETTE
+            .iter()
+            .flat_map(|&c| [(c >> 16) as u8, (c >> 8) as u8, c as u8])
+            .collect();
+        let width = (WIDTH / options.scale) as u16;
+        let height = (HEIGHT / options.scale) as u16;
+        let mut encoder =
+            gif::Encoder::new(writer, width, height, &palette).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        Ok(GifRecorder {
+            encoder,
+            renderer: BarRenderer::new(num_bars, bar_width),
+            quantizer: PaletteQuantizer::default(),
+            options,
+            frames: 0,
+            error: None,
+        })
+    }
+
+    // Draws the view of the algorithm (e.g. its auxiliary arrays) into every frame
+    pub fn set_view(&mut self, view: Box<dyn SortView>) {
+        self.renderer.set_view(view);
+    }
+
+    // Writes the GIF trailer and hands back the writer
+    // Returns the first error that happened while writing the frames instead, if any
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => self.encoder.into_inner(),
+        }
+    }
+
+    // Shrinks the current frame, maps it to the palette and appends it to the animation
+    fn write_frame(&mut self, delay_cs: u16) {
+        let scale = self.options.scale;
+        let width = WIDTH / scale;
+        let height = HEIGHT / scale;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = self.renderer.buffer[y * scale * WIDTH + x * scale];
+                pixels.push(self.quantizer.index_of(color));
+            }
+        }
+        let mut frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, pixels, None);
+        frame.delay = delay_cs;
+        if let Err(error) = self.encoder.write_frame(&frame) {
+            self.error = Some(io::Error::other(error));
+        }
+    }
+}
+
+impl<W: Write> SortObserver for GifRecorder<W> {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        if self.error.is_some() || !self.renderer.apply(array, event) {
+            return ControlFlow::Continue(());
+        }
+        if event == SortEvent::Done {
+            // The finished array is always part of the animation
+            self.write_frame(GIF_FINAL_DELAY_CS);
+        } else if self.frames.is_multiple_of(self.options.frame_step) {
+            self.write_frame(self.options.delay_cs);
+        }
+        self.frames += 1;
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,87 @@
+use crate::draw::darken_rect;
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::render::SortView;
+use crate::WIDTH;
+use std::ops::ControlFlow;
+
+const UNSORTED_COLOR: u32 = 0x0000FF; // The color draw_bars uses for unsorted bars
+
+// Colors of neighbouring subsequences, so the interleaved subsequences of a gap stand apart
+const SUBSEQUENCE_COLORS: [u32; 8] = [
+    0x0000FF, 0x00A0FF, 0xA000FF, 0xFF8000, 0x00C080, 0xFF40A0, 0xC0C000, 0x8080FF,
+];
+
+const LABEL_SCALE: usize = 3;
+const LABEL_MARGIN: usize = 12;
+
+// Colors the unsorted bars by the subsequence they belong to in the current pass
+// (index % gap) and shows the gap, for algorithms that report their gaps (e.g. Shell Sort)
+pub struct GapView {
+    bar_width: usize,
+    gap: Option<usize>,
+}
+
+impl GapView {
+    // num_bars: The number of elements being sorted
+    pub fn new(num_bars: usize) -> Self {
+        GapView {
+            bar_width: WIDTH / num_bars.max(1),
+            gap: None,
+        }
+    }
+
+    // The gap of the current pass
+    pub fn gap(&self) -> Option<usize> {
+        self.gap
+    }
+}
+
+impl SortObserver for GapView {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        match event {
+            SortEvent::Gap(gap) => self.gap = Some(gap),
+            SortEvent::Done => self.gap = None,
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for GapView {
+    fn draw(&self, buffer: &mut [u32]) {
+        let Some(gap) = self.gap else {
+            return;
+        };
+        // Recolor the unsorted bars, leaving the highlighted and sorted ones as they are
+        if gap > 1 {
+            for row in buffer.chunks_exact_mut(WIDTH) {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    if *pixel == UNSORTED_COLOR {
+                        let subsequence = x / self.bar_width % gap;
+                        *pixel = SUBSEQUENCE_COLORS[subsequence % SUBSEQUENCE_COLORS.len()];
+                    }
+                }
+            }
+        }
+        let label = format!("Gap: {}", gap);
+        let width = text_width(&label, LABEL_SCALE);
+        let height = GLYPH_HEIGHT * LABEL_SCALE;
+        let x = WIDTH - width - 2 * LABEL_MARGIN;
+        darken_rect(
+            buffer,
+            x - LABEL_MARGIN,
+            LABEL_MARGIN / 2,
+            width + 2 * LABEL_MARGIN,
+            height + 2 * LABEL_MARGIN,
+        );
+        draw_text(
+            buffer,
+            x,
+            LABEL_MARGIN * 3 / 2,
+            &label,
+            LABEL_SCALE,
+            0xFFFFFF,
+        );
+    }
+}
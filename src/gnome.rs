@@ -0,0 +1,27 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Implements Gnome Sort, reporting every step to the observer
+// A single position walks forward while neighbours are in order, and swaps an element
+// backwards (stepping back with it) until it is in order again
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the comparisons and swaps as events
+pub fn gnome_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    let mut pos = 1;
+    while pos < n {
+        observer.on_event(array, SortEvent::Compare(pos - 1, pos))?;
+        if array[pos - 1] <= array[pos] {
+            pos += 1;
+        } else {
+            array.swap(pos - 1, pos);
+            observer.on_event(array, SortEvent::Swap(pos - 1, pos))?;
+            // Step back with the element, the start of the array is always in order
+            pos = (pos - 1).max(1);
+        }
+    }
+    observer.on_event(array, SortEvent::Done)
+}
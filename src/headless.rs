@@ -0,0 +1,75 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::render::{BarRenderer, SortView};
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+
+// Observer that renders every frame into an in-memory framebuffer instead of a window
+pub struct HeadlessRecorder {
+    renderer: BarRenderer,
+    pub frames: u64, // Number of frames rendered so far
+}
+
+impl HeadlessRecorder {
+    // num_bars: The total number of bars
+    // bar_width: The calculated width of each bar
+    pub fn new(num_bars: usize, bar_width: usize) -> Self {
+        HeadlessRecorder {
+            renderer: BarRenderer::new(num_bars, bar_width),
+            frames: 0,
+        }
+    }
+
+    // Draws the view of the algorithm (e.g. its auxiliary arrays) into every frame
+    pub fn set_view(&mut self, view: Box<dyn SortView>) {
+        self.renderer.set_view(view);
+    }
+
+    // The pixel buffer holding the last rendered frame
+    pub fn buffer(&self) -> &[u32] {
+        &self.renderer.buffer
+    }
+}
+
+impl SortObserver for HeadlessRecorder {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        if self.renderer.apply(array, event) {
+            self.frames += 1;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+// Observer that writes every event as one line of text
+// The first write error is kept and reported by finish(), later events are dropped
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TraceWriter<W> {
+    // writer: Where the trace is written
+    // array: The initial array, written as the first line of the trace
+    pub fn new(mut writer: W, array: &[u32]) -> Self {
+        let values: Vec<String> = array.iter().map(|value| value.to_string()).collect();
+        let error = writeln!(writer, "array {}", values.join(" ")).err();
+        TraceWriter { writer, error }
+    }
+
+    // Flushes the trace and hands back the writer
+    // Returns the first error that happened while writing the trace instead, if any
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => self.writer.flush().map(|()| self.writer),
+        }
+    }
+}
+
+impl<W: Write> SortObserver for TraceWriter<W> {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        if self.error.is_none() {
+            self.error = writeln!(self.writer, "{}", event).err();
+        }
+        ControlFlow::Continue(())
+    }
+}
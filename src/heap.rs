@@ -0,0 +1,76 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Moves the element at root down the max-heap array[low..low + size] until both children are smaller
+// array: The array holding the heap (mutable)
+// low: Index of the first element of the heap (the top of the heap)
+// root: Index of the element to sift down, relative to low
+// size: Number of elements in the heap
+pub fn sift_down(
+    array: &mut [u32],
+    low: usize,
+    mut root: usize,
+    size: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    loop {
+        let left = 2 * root + 1;
+        let right = left + 1;
+        if left >= size {
+            break;
+        }
+        // Find the largest of the root and its children
+        let mut largest = root;
+        observer.on_event(array, SortEvent::Compare(low + left, low + largest))?;
+        if array[low + left] > array[low + largest] {
+            largest = left;
+        }
+        if right < size {
+            observer.on_event(array, SortEvent::Compare(low + right, low + largest))?;
+            if array[low + right] > array[low + largest] {
+                largest = right;
+            }
+        }
+        if largest == root {
+            break;
+        }
+        array.swap(low + root, low + largest);
+        observer.on_event(array, SortEvent::Swap(low + root, low + largest))?;
+        root = largest;
+    }
+    ControlFlow::Continue(())
+}
+
+// Heap Sorts array[low..high], marking every element of the range as sorted
+// Used on its own and as the fallback of the hybrid sorts (e.g. Introsort)
+pub fn heap_sort_range(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = high - low;
+    // Build a max-heap, sifting down every parent from the last one to the root
+    for i in (0..n / 2).rev() {
+        sift_down(array, low, i, n, observer)?;
+    }
+    // Move the largest element to the end of the heap and restore the heap on the rest
+    for end in (1..n).rev() {
+        array.swap(low, low + end);
+        observer.on_event(array, SortEvent::Swap(low, low + end))?;
+        observer.on_event(array, SortEvent::MarkSorted(low + end))?;
+        sift_down(array, low, 0, end, observer)?;
+    }
+    if n > 0 {
+        observer.on_event(array, SortEvent::MarkSorted(low))?;
+    }
+    ControlFlow::Continue(())
+}
+
+// Implements Heap Sort, reporting every step to the observer
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the comparisons, swaps and sorted positions as events
+pub fn heap_sort_visualized(array: &mut [u32], observer: &mut dyn SortObserver) -> ControlFlow<()> {
+    heap_sort_range(array, 0, array.len(), observer)?;
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,123 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::render::{apply_pivots, draws_frame};
+use std::ops::ControlFlow;
+
+// Number of events between two snapshots of the array
+// A state is rebuilt from the closest snapshot by replaying at most this many events
+const SNAPSHOT_INTERVAL: usize = 1024;
+
+// The state of a sort after one of its operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryState {
+    pub array: Vec<u32>,
+    pub sorted: Vec<bool>,  // Elements that reached their final position
+    pub pivots: Vec<usize>, // Pivots of the partition at that point
+    pub event: SortEvent,   // The operation that led to this state
+}
+
+// A copy of the array and the sorted flags after a number of events
+struct Snapshot {
+    array: Vec<u32>,
+    sorted: Vec<bool>,
+    pivots: Vec<usize>,
+}
+
+// Records a sort so the state after any of its operations can be rebuilt later,
+// using periodic snapshots plus replay of the events since the snapshot
+pub struct History {
+    events: Vec<SortEvent>,
+    operations: Vec<usize>, // Indices into events of the events that draw a frame
+    snapshots: Vec<Snapshot>, // State after 0, SNAPSHOT_INTERVAL, 2 * SNAPSHOT_INTERVAL, ... events
+    sorted: Vec<bool>,      // Sorted flags after the last recorded event
+    pivots: Vec<usize>,     // Pivots after the last recorded event
+}
+
+impl History {
+    // initial: The array before the sort started
+    pub fn new(initial: &[u32]) -> Self {
+        History {
+            events: Vec::new(),
+            operations: Vec::new(),
+            snapshots: vec![Snapshot {
+                array: initial.to_vec(),
+                sorted: vec![false; initial.len()],
+                pivots: Vec::new(),
+            }],
+            sorted: vec![false; initial.len()],
+            pivots: Vec::new(),
+        }
+    }
+
+    // Number of recorded operations (events that draw a frame)
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    // Adds an event to the history
+    // array: The state of the array right after the event
+    pub fn record(&mut self, array: &[u32], event: SortEvent) {
+        apply_sorted(&mut self.sorted, event);
+        apply_pivots(&mut self.pivots, event);
+        if draws_frame(event) {
+            self.operations.push(self.events.len());
+        }
+        self.events.push(event);
+        if self.events.len().is_multiple_of(SNAPSHOT_INTERVAL) {
+            self.snapshots.push(Snapshot {
+                array: array.to_vec(),
+                sorted: self.sorted.clone(),
+                pivots: self.pivots.clone(),
+            });
+        }
+    }
+
+    // Rebuilds the state right after an operation
+    // operation: Index of the operation, from 0 to len() - 1
+    pub fn state_at(&self, operation: usize) -> HistoryState {
+        let last = self.operations[operation];
+        let snapshot = &self.snapshots[(last + 1) / SNAPSHOT_INTERVAL];
+        let mut array = snapshot.array.clone();
+        let mut sorted = snapshot.sorted.clone();
+        let mut pivots = snapshot.pivots.clone();
+        let first = (last + 1) / SNAPSHOT_INTERVAL * SNAPSHOT_INTERVAL;
+        for &event in &self.events[first..=last] {
+            match event {
+                SortEvent::Swap(a, b) => array.swap(a, b),
+                SortEvent::Write(i, value) => array[i] = value,
+                _ => {}
+            }
+            apply_sorted(&mut sorted, event);
+            apply_pivots(&mut pivots, event);
+        }
+        HistoryState {
+            array,
+            sorted,
+            pivots,
+            event: self.events[last],
+        }
+    }
+}
+
+// Updates the sorted flags the same way the renderer does
+fn apply_sorted(sorted: &mut [bool], event: SortEvent) {
+    match event {
+        SortEvent::MarkSorted(i) => {
+            if let Some(flag) = sorted.get_mut(i) {
+                *flag = true;
+            }
+        }
+        SortEvent::Done => sorted.fill(true),
+        _ => {}
+    }
+}
+
+impl SortObserver for History {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        self.record(array, event);
+        ControlFlow::Continue(())
+    }
+}
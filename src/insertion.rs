@@ -1,62 +1,71 @@
-use crate::{draw_bars, play_tone};
-use minifb::Window;
-use rodio::Sink;
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
 
-// Define constants for the window resolution
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1080;
+// Inserts array[i] into the sorted run array[low..i], shifting the greater elements one position right
+// Shared with the hybrid sorts that finish small partitions with Insertion Sort
+pub fn insert(
+    array: &mut [u32],
+    low: usize,
+    i: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let key = array[i]; // The element to be inserted
+    let mut j = i; // Index for comparison
+
+    // Move elements of array[low..i], that are greater than key,
+    // to one position ahead of their current position
+    while j > low {
+        observer.on_event(array, SortEvent::Compare(j - 1, j))?;
+        if array[j - 1] <= key {
+            break;
+        }
+        array[j] = array[j - 1]; // Shift element to the right
+        observer.on_event(array, SortEvent::Write(j, array[j]))?;
+        j -= 1;
+    }
+    // Place the key at its correct position (only needed if a shift occurred)
+    if j != i {
+        array[j] = key;
+        observer.on_event(array, SortEvent::Write(j, key))?;
+    }
+    ControlFlow::Continue(())
+}
+
+// Insertion Sorts array[low..high], marking every element of the range as sorted once it is done
+// The caller must know the range holds exactly the elements that belong there
+// (e.g. a partition of a Quick Sort)
+pub fn insertion_sort_range(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    for i in low + 1..high {
+        insert(array, low, i, observer)?;
+    }
+    for i in low..high {
+        observer.on_event(array, SortEvent::MarkSorted(i))?;
+    }
+    ControlFlow::Continue(())
+}
 
-// Implements the Insertion Sort algorithm with visualization steps
+// Implements the Insertion Sort algorithm, reporting every step to the observer
 // array: The array of numbers to be sorted (mutable)
-// window: The minifb window to update (mutable)
-// buffer: The pixel buffer (mutable)
-// sink: The audio sink to play sounds on (mutable reference)
-// use_sound: A boolean flag to control sound playback
-// bar_width: The calculated width of each bar
-// num_bars: The total number of bars
+// observer: Receives the comparisons, shifts and sorted prefix as events
 pub fn insertion_sort_visualized(
     array: &mut [u32],
-    window: &mut Window,
-    buffer: &mut [u32],
-    sink: &Sink,
-    use_sound: bool,
-    bar_width: usize,
-    num_bars: usize,
-) {
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
     let n = array.len();
+    if n > 0 {
+        // A single element is a sorted prefix on its own
+        observer.on_event(array, SortEvent::MarkSorted(0))?;
+    }
     // Iterate from the second element to the end of the array
     for i in 1..n {
-        let key = array[i]; // The element to be inserted
-        let mut j = i; // Index for comparison
-
-        // Move elements of array[0..i-1], that are greater than key,
-        // to one position ahead of their current position
-        while j > 0 && array[j - 1] > key {
-            if use_sound {
-                // Play a tone for each comparison/shift
-                play_tone(sink, array[j - 1], 15, num_bars);
-            }
-            array[j] = array[j - 1]; // Shift element to the right
-            j -= 1;
-
-            // Visualize the shift
-            draw_bars(array, buffer, Some(j), Some(j + 1), Some(i), bar_width);
-            window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
-        }
-        // Place the key at its correct position
-        if array[j] != key {
-            // Only update if a shift occurred or it's the first element
-            array[j] = key;
-            if use_sound {
-                // Play a tone for the final placement of the key
-                play_tone(sink, array[j], 15, num_bars);
-            }
-            draw_bars(array, buffer, Some(j), None, Some(i + 1), bar_width);
-            window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
-        } else {
-            // If no shifts occurred, still update visualization for 'i' being sorted
-            draw_bars(array, buffer, None, None, Some(i + 1), bar_width);
-            window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
-        }
+        insert(array, 0, i, observer)?;
+        // The prefix array[0..=i] is now sorted
+        observer.on_event(array, SortEvent::MarkSorted(i))?;
     }
+    observer.on_event(array, SortEvent::Done)
 }
@@ -0,0 +1,67 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::heap::heap_sort_range;
+use crate::insertion::insertion_sort_range;
+use crate::quick::partition;
+use std::ops::ControlFlow;
+
+// Partitions of at most this many elements are finished with Insertion Sort
+const INSERTION_THRESHOLD: usize = 16;
+
+// Moves the median of the first, middle and last element of array[low..high] to the end,
+// so it becomes the pivot of the partition
+fn median_of_three_to_end(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let (a, b, c) = (low, low + (high - low) / 2, high - 1);
+    // Sort the three candidates in place, leaving the median in the middle
+    for (x, y) in [(a, b), (b, c), (a, b)] {
+        observer.on_event(array, SortEvent::Compare(x, y))?;
+        if array[x] > array[y] {
+            array.swap(x, y);
+            observer.on_event(array, SortEvent::Swap(x, y))?;
+        }
+    }
+    array.swap(b, c);
+    observer.on_event(array, SortEvent::Swap(b, c))
+}
+
+// Sorts array[low..high] with Quick Sort until the depth limit runs out
+fn introsort_recursive(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    depth_limit: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    if high - low <= INSERTION_THRESHOLD {
+        observer.on_event(array, SortEvent::Phase("Insertion Sort"))?;
+        return insertion_sort_range(array, low, high, observer);
+    }
+    if depth_limit == 0 {
+        // Too many unbalanced partitions, Heap Sort keeps the worst case at O(n log n)
+        observer.on_event(array, SortEvent::Phase("Heap Sort fallback"))?;
+        return heap_sort_range(array, low, high, observer);
+    }
+    observer.on_event(array, SortEvent::Phase("Quick Sort"))?;
+    median_of_three_to_end(array, low, high, observer)?;
+    let pi = partition(array, low, high - 1, observer)?;
+    // The pivot is now in its final position
+    observer.on_event(array, SortEvent::MarkSorted(pi))?;
+    introsort_recursive(array, low, pi, depth_limit - 1, observer)?;
+    introsort_recursive(array, pi + 1, high, depth_limit - 1, observer)
+}
+
+// Implements Introsort, reporting every step to the observer
+// Quick Sort with a median of three pivot, falling back to Heap Sort once the recursion gets
+// deeper than 2 * log2(n), and finishing small partitions with Insertion Sort
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the comparisons, swaps, phases and sorted positions as events
+pub fn introsort_visualized(array: &mut [u32], observer: &mut dyn SortObserver) -> ControlFlow<()> {
+    let n = array.len();
+    let depth_limit = 2 * n.max(1).ilog2() as usize;
+    introsort_recursive(array, 0, n, depth_limit, observer)?;
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,140 @@
+use crate::draw::{darken_rect, draw_line, fill_rect};
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, GLYPH_HEIGHT};
+use crate::render::SortView;
+use crate::smooth::leonardo;
+use crate::WIDTH;
+use std::ops::ControlFlow;
+
+// Area of the window used by the forest, above the bars
+const FOREST_TOP: usize = 12;
+const LEVEL_HEIGHT: usize = 24;
+const TEXT_SCALE: usize = 2;
+const TITLE_HEIGHT: usize = (GLYPH_HEIGHT + 6) * TEXT_SCALE;
+const MAX_NODE_SIZE: usize = 10;
+
+const TREE_COLORS: [u32; 2] = [0x4080FF, 0xA000FF]; // Neighbouring trees of the forest
+const ROOT_COLOR: u32 = 0xFFFF00; // The roots, ascending from left to right
+const HIGHLIGHT_COLOR: u32 = 0xFF0000; // Nodes touched by the last operation
+const EDGE_COLOR: u32 = 0x808080;
+const TEXT_COLOR: u32 = 0xFFFFFF;
+
+// Draws the forest of Leonardo heaps of Smoothsort above the array: every node sits over the
+// bar of its element, its children below it, so the trees grow from left to right during
+// construction and fall apart into their subtrees during teardown
+pub struct LeonardoView {
+    bar_width: usize,
+    trees: Vec<(usize, usize)>, // Start and length of the trees, by start
+    highlights: (Option<usize>, Option<usize>),
+    levels: usize, // Levels of the largest tree that fits into the array
+}
+
+impl LeonardoView {
+    // num_bars: The number of elements being sorted
+    pub fn new(num_bars: usize) -> Self {
+        let orders = (1..).take_while(|&order| leonardo(order) <= num_bars.max(1));
+        LeonardoView {
+            bar_width: WIDTH / num_bars.max(1),
+            trees: Vec::new(),
+            highlights: (None, None),
+            levels: orders.last().unwrap_or(1),
+        }
+    }
+
+    // Start and length of the trees of the forest, from left to right
+    pub fn trees(&self) -> &[(usize, usize)] {
+        &self.trees
+    }
+
+    // Center of the node of an element at a depth
+    fn node_position(&self, i: usize, depth: usize) -> (usize, usize) {
+        let x = i * self.bar_width + self.bar_width / 2;
+        (x, FOREST_TOP + TITLE_HEIGHT + depth * LEVEL_HEIGHT)
+    }
+}
+
+impl SortObserver for LeonardoView {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        match event {
+            SortEvent::Tree(start, len) => {
+                // The new tree replaces the ones it overlaps (e.g. the two it was built from)
+                self.trees.retain(|&(other, other_len)| {
+                    other + other_len <= start || start + len <= other
+                });
+                let index = self.trees.partition_point(|&(other, _)| other < start);
+                self.trees.insert(index, (start, len));
+            }
+            // A sorted element left the forest with the tree it was the only node of
+            SortEvent::MarkSorted(i) => self.trees.retain(|&(start, len)| start + len <= i),
+            SortEvent::Compare(a, b) | SortEvent::Swap(a, b) => {
+                self.highlights = (Some(a), Some(b));
+            }
+            SortEvent::Done => {
+                self.trees.clear();
+                self.highlights = (None, None);
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for LeonardoView {
+    fn draw(&self, buffer: &mut [u32]) {
+        darken_rect(
+            buffer,
+            0,
+            FOREST_TOP - 6,
+            WIDTH,
+            TITLE_HEIGHT + self.levels * LEVEL_HEIGHT + 6,
+        );
+        let sizes: Vec<String> = self.trees.iter().map(|(_, len)| len.to_string()).collect();
+        let title = format!("Leonardo heaps: {}", sizes.join(" "));
+        draw_text(buffer, 12, FOREST_TOP, &title, TEXT_SCALE, TEXT_COLOR);
+
+        // Every node with its depth and the tree it belongs to, walking the trees from their roots
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for (index, &(start, len)) in self.trees.iter().enumerate() {
+            let order = (1..).find(|&order| leonardo(order) >= len).unwrap_or(1);
+            let mut stack = vec![(start, order, 0)];
+            while let Some((start, order, depth)) = stack.pop() {
+                let root = start + leonardo(order) - 1;
+                nodes.push((root, depth, index));
+                if order >= 2 {
+                    let right = start + leonardo(order - 1);
+                    edges.push(((root, depth), (right + leonardo(order - 2) - 1, depth + 1)));
+                    edges.push(((root, depth), (right - 1, depth + 1)));
+                    stack.push((start, order - 1, depth + 1));
+                    stack.push((right, order - 2, depth + 1));
+                }
+            }
+        }
+
+        // Edges first, so the nodes are drawn over them
+        for ((parent, parent_depth), (child, child_depth)) in edges {
+            let from = self.node_position(parent, parent_depth);
+            let to = self.node_position(child, child_depth);
+            draw_line(buffer, from, to, EDGE_COLOR);
+        }
+        let size = self.bar_width.saturating_sub(2).clamp(2, MAX_NODE_SIZE);
+        for (i, depth, index) in nodes {
+            let color = if Some(i) == self.highlights.0 || Some(i) == self.highlights.1 {
+                HIGHLIGHT_COLOR
+            } else if depth == 0 {
+                ROOT_COLOR
+            } else {
+                TREE_COLORS[index % TREE_COLORS.len()]
+            };
+            let (x, y) = self.node_position(i, depth);
+            fill_rect(
+                buffer,
+                x.saturating_sub(size / 2),
+                y - size / 2,
+                size,
+                size,
+                color,
+            );
+        }
+    }
+}
@@ -1,33 +1,449 @@
-use rodio::{Sink, Source};
-use std::time::Duration;
+use events::SortObserver;
+use quick::{PartitionScheme, PivotStrategy};
+use render::SortView;
+use shell::GapSequence;
+use std::ops::ControlFlow;
 
 #[cfg(test)]
 pub mod tests;
 
+pub mod audio;
+pub mod auxiliary;
+pub mod block;
+pub mod bogo;
 pub mod bubble;
+pub mod bucket;
+pub mod budget;
+pub mod cocktail;
+pub mod comb;
+pub mod cost;
+pub mod counting;
+pub mod cycle;
+pub mod diagram;
+pub mod display;
+pub mod draw;
+pub mod events;
+pub mod export;
+pub mod font;
+pub mod gaps;
+pub mod gnome;
+pub mod headless;
+pub mod heap;
+pub mod history;
 pub mod insertion;
+pub mod introsort;
+pub mod leonardo;
 pub mod merge;
+pub mod network;
+pub mod odd_even;
+pub mod overlay;
+pub mod pancake;
+pub mod pdq;
+pub mod phase;
 pub mod quick;
+pub mod radix;
+pub mod render;
+pub mod runs;
 pub mod selection;
+pub mod shell;
+pub mod slowsort;
+pub mod smooth;
+pub mod stats;
+pub mod stooge;
+pub mod tim;
+pub mod tree;
+pub mod video;
+pub mod wav;
 
 // Define constants for the window resolution
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1080;
+pub const WIDTH: usize = 1920;
+pub const HEIGHT: usize = 1080;
+
+// A sorting algorithm that can be picked from the menu
+// name: The name shown to the user
+// sort: Sorts the array and reports every step to the observer (breaks if the observer aborted it)
+//   The seed drives the random choices of the algorithm (e.g. random pivots), so runs can be repeated
+// tone_ms: The duration of the tone played for each step when sound is enabled
+// view: Creates the algorithm specific overlay for a number of bars, if it has one
+// family: The entry of the menu the algorithm is listed under (variants of an algorithm share it)
+pub struct Algorithm {
+    pub name: &'static str,
+    pub family: &'static str,
+    pub sort: fn(&mut [u32], u64, &mut dyn SortObserver) -> ControlFlow<()>,
+    pub tone_ms: u64,
+    // The most bars the algorithm is run with, for algorithms that would not finish with more
+    pub max_bars: Option<usize>,
+    pub view: Option<fn(usize) -> Box<dyn SortView>>,
+}
+
+// A Quick Sort variant with its partition scheme and pivot strategy, listed under Quick Sort
+macro_rules! quick_sort {
+    ($scheme:ident, $strategy:ident, $label:literal) => {
+        Algorithm {
+            name: concat!("Quick Sort (", $label, ")"),
+            family: "Quick Sort",
+            sort: |array, seed, observer| {
+                quick::quick_sort_with(
+                    array,
+                    PartitionScheme::$scheme,
+                    PivotStrategy::$strategy,
+                    seed,
+                    observer,
+                )
+            },
+            tone_ms: 5, // Shorter tone for faster Quick Sort
+            max_bars: None,
+            view: None,
+        }
+    };
+}
+
+// A Shell Sort variant with its gap sequence, listed under Shell Sort
+macro_rules! shell_sort {
+    ($gaps:ident, $label:literal) => {
+        Algorithm {
+            name: concat!("Shell Sort (", $label, " gaps)"),
+            family: "Shell Sort",
+            sort: |array, _, observer| {
+                shell::shell_sort_visualized(array, GapSequence::$gaps, observer)
+            },
+            tone_ms: 5,
+            max_bars: None,
+            view: Some(|num_bars| Box::new(gaps::GapView::new(num_bars))),
+        }
+    };
+}
+
+// A Radix Sort variant with its digit order and base, listed under Radix Sort
+macro_rules! radix_sort {
+    ($sort:ident, $order:literal, $base:literal) => {
+        Algorithm {
+            name: concat!("Radix Sort (", $order, ", base ", $base, ")"),
+            family: "Radix Sort",
+            sort: |array, _, observer| radix::$sort(array, $base, observer),
+            tone_ms: 5,
+            max_bars: None,
+            view: Some(|_| Box::new(auxiliary::AuxView::new(&["Counts", "Buckets"]))),
+        }
+    };
+}
+
+// A Comb Sort variant with its shrink factor, listed under Comb Sort
+macro_rules! comb_sort {
+    ($shrink:expr, $label:literal) => {
+        Algorithm {
+            name: concat!("Comb Sort (shrink ", $label, ")"),
+            family: "Comb Sort",
+            sort: |array, _, observer| comb::comb_sort_visualized(array, $shrink, observer),
+            tone_ms: 5,
+            max_bars: None,
+            view: Some(|num_bars| Box::new(gaps::GapView::new(num_bars))),
+        }
+    };
+}
+
+// Every algorithm offered by the visualizer, in menu order (the variants of a family next to each other)
+pub const ALGORITHMS: &[Algorithm] = &[
+    Algorithm {
+        name: "Bubble Sort",
+        family: "Bubble Sort",
+        sort: |array, _, observer| bubble::bubble_sort_visualized(array, observer),
+        tone_ms: 15,
+        max_bars: None,
+        view: None,
+    },
+    Algorithm {
+        name: "Selection Sort",
+        family: "Selection Sort",
+        sort: |array, _, observer| selection::selection_sort_visualized(array, observer),
+        tone_ms: 15,
+        max_bars: None,
+        view: None,
+    },
+    Algorithm {
+        name: "Insertion Sort",
+        family: "Insertion Sort",
+        sort: |array, _, observer| insertion::insertion_sort_visualized(array, observer),
+        tone_ms: 15,
+        max_bars: None,
+        view: None,
+    },
+    Algorithm {
+        name: "Merge Sort (top-down)",
+        family: "Merge Sort",
+        sort: |array, _, observer| merge::merge_sort_visualized(array, observer),
+        tone_ms: 5, // Shorter tone for faster merge
+        max_bars: None,
+        view: Some(|num_bars| {
+            Box::new(auxiliary::AuxView::new(&["L", "R"]).with_array_scale(num_bars))
+        }),
+    },
+    Algorithm {
+        name: "Merge Sort (bottom-up)",
+        family: "Merge Sort",
+        sort: |array, _, observer| merge::merge_sort_bottom_up_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| {
+            Box::new(auxiliary::AuxView::new(&["L", "R"]).with_array_scale(num_bars))
+        }),
+    },
+    Algorithm {
+        name: "Merge Sort (natural)",
+        family: "Merge Sort",
+        sort: |array, _, observer| merge::natural_merge_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| {
+            Box::new(auxiliary::AuxView::new(&["L", "R"]).with_array_scale(num_bars))
+        }),
+    },
+    Algorithm {
+        name: "Merge Sort (in-place)",
+        family: "Merge Sort",
+        sort: |array, _, observer| merge::merge_sort_in_place_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: None, // No buffers to show
+    },
+    quick_sort!(Lomuto, Last, "Lomuto, last"),
+    quick_sort!(Lomuto, First, "Lomuto, first"),
+    quick_sort!(Lomuto, Middle, "Lomuto, middle"),
+    quick_sort!(Lomuto, Random, "Lomuto, random"),
+    quick_sort!(Lomuto, MedianOfThree, "Lomuto, median of 3"),
+    quick_sort!(Lomuto, Ninther, "Lomuto, ninther"),
+    quick_sort!(Hoare, Last, "Hoare, last"),
+    quick_sort!(Hoare, First, "Hoare, first"),
+    quick_sort!(Hoare, Middle, "Hoare, middle"),
+    quick_sort!(Hoare, Random, "Hoare, random"),
+    quick_sort!(Hoare, MedianOfThree, "Hoare, median of 3"),
+    quick_sort!(Hoare, Ninther, "Hoare, ninther"),
+    quick_sort!(ThreeWay, Last, "3-way, last"),
+    quick_sort!(ThreeWay, First, "3-way, first"),
+    quick_sort!(ThreeWay, Middle, "3-way, middle"),
+    quick_sort!(ThreeWay, Random, "3-way, random"),
+    quick_sort!(ThreeWay, MedianOfThree, "3-way, median of 3"),
+    quick_sort!(ThreeWay, Ninther, "3-way, ninther"),
+    quick_sort!(DualPivot, Last, "dual-pivot, last"),
+    quick_sort!(DualPivot, First, "dual-pivot, first"),
+    quick_sort!(DualPivot, Middle, "dual-pivot, middle"),
+    quick_sort!(DualPivot, Random, "dual-pivot, random"),
+    quick_sort!(DualPivot, MedianOfThree, "dual-pivot, median of 3"),
+    quick_sort!(DualPivot, Ninther, "dual-pivot, ninther"),
+    Algorithm {
+        name: "Heap Sort",
+        family: "Heap Sort",
+        sort: |array, _, observer| heap::heap_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| Box::new(tree::HeapTreeView::new(num_bars))),
+    },
+    Algorithm {
+        name: "Smoothsort",
+        family: "Heap Sort",
+        sort: |array, _, observer| smooth::smooth_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| Box::new(leonardo::LeonardoView::new(num_bars))),
+    },
+    shell_sort!(Shell, "Shell"),
+    shell_sort!(Knuth, "Knuth"),
+    shell_sort!(Sedgewick, "Sedgewick"),
+    shell_sort!(Ciura, "Ciura"),
+    shell_sort!(Tokuda, "Tokuda"),
+    radix_sort!(radix_lsd_sort_visualized, "LSD", 2),
+    radix_sort!(radix_lsd_sort_visualized, "LSD", 4),
+    radix_sort!(radix_lsd_sort_visualized, "LSD", 10),
+    radix_sort!(radix_lsd_sort_visualized, "LSD", 16),
+    radix_sort!(radix_lsd_sort_visualized, "LSD", 256),
+    radix_sort!(radix_msd_sort_visualized, "MSD", 2),
+    radix_sort!(radix_msd_sort_visualized, "MSD", 4),
+    radix_sort!(radix_msd_sort_visualized, "MSD", 10),
+    radix_sort!(radix_msd_sort_visualized, "MSD", 16),
+    radix_sort!(radix_msd_sort_visualized, "MSD", 256),
+    Algorithm {
+        name: "Counting Sort",
+        family: "Counting Sort",
+        sort: |array, _, observer| counting::counting_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|_| Box::new(auxiliary::AuxView::new(&["Counts"]))),
+    },
+    Algorithm {
+        name: "Bucket Sort",
+        family: "Bucket Sort",
+        sort: |array, _, observer| bucket::bucket_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| {
+            Box::new(auxiliary::AuxView::columns(
+                "Buckets",
+                bucket::bucket_count(num_bars),
+            ))
+        }),
+    },
+    Algorithm {
+        name: "Cocktail Shaker Sort",
+        family: "Cocktail Shaker Sort",
+        sort: |array, _, observer| cocktail::cocktail_shaker_sort_visualized(array, observer),
+        tone_ms: 15,
+        max_bars: None,
+        view: None,
+    },
+    comb_sort!(comb::DEFAULT_SHRINK, "1.3"),
+    comb_sort!(1.5, "1.5"),
+    comb_sort!(2.0, "2.0"),
+    Algorithm {
+        name: "Gnome Sort",
+        family: "Gnome Sort",
+        sort: |array, _, observer| gnome::gnome_sort_visualized(array, observer),
+        tone_ms: 15,
+        max_bars: None,
+        view: None,
+    },
+    Algorithm {
+        name: "Odd-Even Transposition Sort",
+        family: "Odd-Even Transposition Sort",
+        sort: |array, _, observer| odd_even::odd_even_sort_visualized(array, observer),
+        tone_ms: 15,
+        max_bars: None,
+        view: None,
+    },
+    Algorithm {
+        name: "Introsort",
+        family: "Introsort",
+        sort: |array, _, observer| introsort::introsort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|_| Box::new(phase::PhaseView::new())),
+    },
+    Algorithm {
+        name: "Pattern-Defeating Quicksort",
+        family: "Pattern-Defeating Quicksort",
+        sort: |array, _, observer| pdq::pdqsort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|_| Box::new(phase::PhaseView::new())),
+    },
+    Algorithm {
+        name: "TimSort",
+        family: "TimSort",
+        sort: |array, _, observer| tim::tim_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| Box::new(runs::RunView::new(num_bars))),
+    },
+    Algorithm {
+        name: "Block Sort",
+        family: "Block Sort",
+        sort: |array, _, observer| block::block_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| Box::new(runs::RunView::blocks(num_bars))),
+    },
+    Algorithm {
+        name: "Bitonic Sort",
+        family: "Sorting Networks",
+        sort: |array, _, observer| network::bitonic_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| {
+            let network = network::bitonic_network(num_bars);
+            Box::new(diagram::NetworkView::new(num_bars, network))
+        }),
+    },
+    Algorithm {
+        name: "Odd-Even Merge Sort",
+        family: "Sorting Networks",
+        sort: |array, _, observer| network::odd_even_merge_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| {
+            let network = network::odd_even_merge_network(num_bars);
+            Box::new(diagram::NetworkView::new(num_bars, network))
+        }),
+    },
+    Algorithm {
+        name: "Cycle Sort",
+        family: "Cycle Sort",
+        sort: |array, _, observer| cycle::cycle_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|_| Box::new(cost::CostView::writes())),
+    },
+    Algorithm {
+        name: "Pancake Sort",
+        family: "Pancake Sort",
+        sort: |array, _, observer| pancake::pancake_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: None,
+        view: Some(|num_bars| Box::new(cost::CostView::flips(num_bars))),
+    },
+    Algorithm {
+        name: "Stooge Sort",
+        family: "Novelty Sorts",
+        sort: |array, _, observer| stooge::stooge_sort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: Some(128),
+        view: Some(|_| Box::new(phase::PhaseView::new())), // Shows when the budget ran out
+    },
+    Algorithm {
+        name: "Slowsort",
+        family: "Novelty Sorts",
+        sort: |array, _, observer| slowsort::slowsort_visualized(array, observer),
+        tone_ms: 5,
+        max_bars: Some(64),
+        view: Some(|_| Box::new(phase::PhaseView::new())), // Shows when the budget ran out
+    },
+    Algorithm {
+        name: "Bogosort",
+        family: "Novelty Sorts",
+        sort: |array, seed, observer| bogo::bogo_sort_visualized(array, seed, observer),
+        tone_ms: 5,
+        max_bars: Some(7),
+        view: Some(|_| Box::new(phase::PhaseView::new())), // Shows when the budget ran out
+    },
+    Algorithm {
+        name: "Bozosort",
+        family: "Novelty Sorts",
+        sort: |array, seed, observer| bogo::bozo_sort_visualized(array, seed, observer),
+        tone_ms: 5,
+        max_bars: Some(7),
+        view: Some(|_| Box::new(phase::PhaseView::new())), // Shows when the budget ran out
+    },
+];
+
+// The families of the menu, in order, with the indices of their algorithms in ALGORITHMS
+pub fn algorithm_families() -> Vec<(&'static str, Vec<usize>)> {
+    let mut families: Vec<(&'static str, Vec<usize>)> = Vec::new();
+    for (i, algorithm) in ALGORITHMS.iter().enumerate() {
+        match families.last_mut() {
+            Some((family, indices)) if *family == algorithm.family => indices.push(i),
+            _ => families.push((algorithm.family, vec![i])),
+        }
+    }
+    families
+}
 
 // Function to draw the bars on the pixel buffer
 // array: The current state of the array to visualize
 // buffer: The pixel buffer ([u32]) where pixels are drawn
 // highlight_idx1: Optional index of the first bar to highlight (e.g., during comparison)
 // highlight_idx2: Optional index of the second bar to highlight (e.g., during comparison or swap)
-// sorted_until_idx: Index up to which elements are considered sorted (e.g., for Bubble Sort, the end of the sorted portion)
+// sorted: Flags for the elements that are in their final sorted position (drawn green)
+// pivots: Indices of the pivots of the current partition (drawn yellow)
 // bar_width: The calculated width of each bar
+// bars_height: Height of the area at the top of the window the bars stand in (HEIGHT for all of it)
+#[allow(clippy::too_many_arguments)]
 pub fn draw_bars(
     array: &[u32],
     buffer: &mut [u32],
     highlight_idx1: Option<usize>,
     highlight_idx2: Option<usize>,
-    sorted_until_idx: Option<usize>,
+    sorted: &[bool],
+    pivots: &[usize],
     bar_width: usize,
+    bars_height: usize,
 ) {
     // Clear the buffer by filling it with black (0x000000)
     buffer.fill(0x000000);
@@ -35,32 +451,31 @@ pub fn draw_bars(
     // Iterate through the array to draw each bar
     for (i, &value) in array.iter().enumerate() {
         // Calculate the height of the bar based on its value.
-        // Normalize the value to fit within the window height.
-        let bar_height = (value as f32 / array.len() as f32 * HEIGHT as f32) as usize;
+        // Normalize the value to fit within the height of the bar area.
+        let bar_height = (value as f32 / array.len() as f32 * bars_height as f32) as usize;
 
         // Calculate the starting X position for the current bar
         let x_start = i * bar_width;
 
         // Determine the color of the bar
-        let color = if Some(i) == highlight_idx1 || Some(i) == highlight_idx2 {
+        let color = if pivots.contains(&i) {
+            // The pivots of the current partition are yellow (0xFFFF00), they are compared
+            // in almost every step so a red highlight would hide them
+            0xFFFF00
+        } else if Some(i) == highlight_idx1 || Some(i) == highlight_idx2 {
             // If the bar is highlighted, use red (0xFF0000)
             0xFF0000 // Hexadecimal literal for red
-        } else if let Some(sorted_idx) = sorted_until_idx {
-            if i >= sorted_idx {
-                // If the bar is in the sorted portion, use green (0x00FF00)
-                0x00FF00 // Hexadecimal literal for green
-            } else {
-                // Default color for unsorted bars is blue (0x0000FF)
-                0x0000FF // Hexadecimal literal for blue
-            }
+        } else if sorted.get(i) == Some(&true) {
+            // If the bar is in its sorted position, use green (0x00FF00)
+            0x00FF00 // Hexadecimal literal for green
         } else {
-            // Default color if no specific highlighting or sorted portion is defined
+            // Default color for unsorted bars is blue (0x0000FF)
             0x0000FF // Hexadecimal literal for blue
         };
 
         // Draw the vertical bar pixel by pixel
-        for y in (HEIGHT - bar_height)..HEIGHT {
-            // From bottom of the window up to bar_height
+        for y in (bars_height - bar_height)..bars_height {
+            // From bottom of the bar area up to bar_height
             for x in x_start..(x_start + bar_width) {
                 // Ensure coordinates are within buffer bounds
                 if x < WIDTH && y < HEIGHT {
@@ -71,25 +486,21 @@ pub fn draw_bars(
     }
 }
 
-// Function to play a short tone
-// sink: The audio sink to append the sound to
-// value: The value of the bar, used to determine pitch
-// duration_ms: The duration of the tone in milliseconds
+// Volume of the tones (0.0 to 1.0)
+pub const TONE_VOLUME: f32 = 0.15;
+
+// Function to get the pitch of the tone for a bar
+// value: The value of the bar
 // num_bars: The total number of bars, used for pitch normalization
-pub fn play_tone(sink: &Sink, value: u32, duration_ms: u64, num_bars: usize) {
+pub fn tone_frequency(value: u32, num_bars: usize) -> f32 {
     // Map the bar value (1 to num_bars) to a frequency range (e.g., 400 Hz to 1600 Hz)
     // This range is chosen to be clearly audible and provide a good sweep.
     let min_freq = 400.0;
     let max_freq = 1600.0;
     let freq_range = max_freq - min_freq;
     // Normalize the value: (value - min_value) / (max_value - min_value)
-    // Here, min_value is 1 and max_value is num_bars
-    let value_normalized = (value - 1) as f32 / (num_bars - 1) as f32;
-    let freq = min_freq + value_normalized * freq_range;
-
-    let source = rodio::source::SineWave::new(freq)
-        .take_duration(Duration::from_millis(duration_ms))
-        .amplify(0.15); // Adjust volume (0.0 to 1.0)
-    sink.append(source);
-    sink.sleep_until_end(); // This will block until the sound has finished playing
+    // Here, min_value is 1 and max_value is num_bars (a single bar gets the lowest pitch)
+    let value_normalized =
+        value.saturating_sub(1) as f32 / num_bars.saturating_sub(1).max(1) as f32;
+    min_freq + value_normalized * freq_range
 }
@@ -1,38 +1,177 @@
 // Import necessary crates and modules
-use minifb::{Key, Window, WindowOptions}; // For creating a window and handling input
-use rand::Rng;
+use minifb::{Window, WindowOptions}; // For creating a window
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rodio::{OutputStream, Sink};
-use sorting_algorithm_visualizer_rs::*;
-use std::io;
+use sorting_algorithm_visualization_rs::audio::{NullAudio, ToneMixer, ToneObserver};
+use sorting_algorithm_visualization_rs::budget::OPERATION_BUDGET;
+use sorting_algorithm_visualization_rs::display::{
+    WindowDisplay, MAX_OPS_PER_SECOND, MIN_OPS_PER_SECOND,
+};
+use sorting_algorithm_visualization_rs::events::{SortEvent, SortObserver};
+use sorting_algorithm_visualization_rs::export::{write_ppm, GifOptions, GifRecorder};
+use sorting_algorithm_visualization_rs::headless::{HeadlessRecorder, TraceWriter};
+use sorting_algorithm_visualization_rs::stats::SortStats;
+use sorting_algorithm_visualization_rs::video::{
+    PpmSequence, VideoOptions, VideoRecorder, Y4mWriter,
+};
+use sorting_algorithm_visualization_rs::wav::WavRenderer;
+use sorting_algorithm_visualization_rs::*;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::ControlFlow;
+use std::process;
+use std::time::Instant;
 
-// Define constants for the window resolution
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1080;
+// Defaults used in headless mode for anything not given on the command line
+const HEADLESS_NUM_BARS: usize = 100;
 
-fn main() {
-    // --- User Input Section ---
+// Initial playback speed of the window, in sort operations per second
+const DEFAULT_OPS_PER_SECOND: f64 = 1000.0;
+
+const USAGE: &str = "Usage: sorting-algorithm-visualization-rs [OPTIONS]
+
+Anything not given on the command line is asked for interactively
+(headless mode uses defaults instead).
+
+Options:
+  --headless          Sort without opening a window or audio device
+  --sound, --no-sound Enable or disable audio feedback
+  --bars <N>          Number of bars (1-1920)
+  --algorithm <N>     Sorting algorithm, by its number in --list-algorithms
+  --list-algorithms   Print the numbers of the algorithms and their variants
+  --speed <N>         Sort operations shown per second (default 1000, +/- change it)
+  --seed <N>          Seed for the random array and the random choices of the algorithms,
+                      to make runs reproducible
+  --trace <PATH>      Headless only: write every sort event to a text file
+  --frame <PATH>      Headless only: write the last frame as a PPM image
+  --gif <PATH>        Export the run as an animated GIF
+  --gif-step <N>      Keep only every N-th frame in the GIF (default 1)
+  --gif-scale <N>     Shrink the GIF frames by this factor (default 4)
+  --gif-delay <N>     Delay between GIF frames in 1/100 s (default 2)
+  --y4m <PATH>        Record the run as a YUV4MPEG2 video ('-' writes to stdout)
+  --ppm-dir <DIR>     Record the run as numbered PPM images in a directory
+  --fps <N>           Frame rate of the recorded video (default 30)
+  --video-step <N>    Keep only every N-th frame in the video (default 1)
+  --wav <PATH>        Render the tones to a WAV file aligned with the video timeline
+  --help              Print this message";
+
+// Options read from the command line
+#[derive(Default)]
+struct Options {
+    headless: bool,
+    sound: Option<bool>,
+    num_bars: Option<usize>,
+    algorithm: Option<&'static Algorithm>,
+    seed: Option<u64>,
+    ops_per_second: Option<f64>,
+    trace: Option<String>,
+    frame: Option<String>,
+    gif: Option<String>,
+    gif_options: GifOptions,
+    y4m: Option<String>,
+    ppm_dir: Option<String>,
+    video_options: VideoOptions,
+    wav: Option<String>,
+}
 
-    // 1. Ask for sound preference
+// Prints an error about the command line and exits
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+// Parses the value following an option
+fn parse_value<T: std::str::FromStr>(args: &mut impl Iterator<Item = String>, name: &str) -> T {
+    args.next()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| usage_error(&format!("Missing or invalid value for {}", name)))
+}
+
+fn parse_args() -> Options {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => options.headless = true,
+            "--sound" => options.sound = Some(true),
+            "--no-sound" => options.sound = Some(false),
+            "--bars" => match parse_value(&mut args, "--bars") {
+                n if n > 0 && n <= WIDTH => options.num_bars = Some(n),
+                _ => usage_error(&format!("--bars must be between 1 and {}", WIDTH)),
+            },
+            "--algorithm" => match parse_value::<usize>(&mut args, "--algorithm") {
+                n if n >= 1 && n <= ALGORITHMS.len() => {
+                    options.algorithm = Some(&ALGORITHMS[n - 1])
+                }
+                _ => usage_error(&format!(
+                    "--algorithm must be between 1 and {}",
+                    ALGORITHMS.len()
+                )),
+            },
+            "--speed" => match parse_value::<f64>(&mut args, "--speed") {
+                speed if (MIN_OPS_PER_SECOND..=MAX_OPS_PER_SECOND).contains(&speed) => {
+                    options.ops_per_second = Some(speed)
+                }
+                _ => usage_error(&format!(
+                    "--speed must be between {} and {}",
+                    MIN_OPS_PER_SECOND, MAX_OPS_PER_SECOND
+                )),
+            },
+            "--seed" => options.seed = Some(parse_value(&mut args, "--seed")),
+            "--trace" => options.trace = Some(parse_value(&mut args, "--trace")),
+            "--frame" => options.frame = Some(parse_value(&mut args, "--frame")),
+            "--gif" => options.gif = Some(parse_value(&mut args, "--gif")),
+            "--gif-step" => options.gif_options.frame_step = parse_value(&mut args, "--gif-step"),
+            "--gif-scale" => options.gif_options.scale = parse_value(&mut args, "--gif-scale"),
+            "--gif-delay" => options.gif_options.delay_cs = parse_value(&mut args, "--gif-delay"),
+            "--y4m" => options.y4m = Some(parse_value(&mut args, "--y4m")),
+            "--ppm-dir" => options.ppm_dir = Some(parse_value(&mut args, "--ppm-dir")),
+            "--fps" => match parse_value(&mut args, "--fps") {
+                0 => usage_error("--fps must be positive"),
+                fps => {
+                    options.video_options.fps = fps;
+                    options.video_options.hold_final_frames = fps as usize;
+                }
+            },
+            "--video-step" => {
+                options.video_options.frame_step = parse_value(&mut args, "--video-step")
+            }
+            "--wav" => options.wav = Some(parse_value(&mut args, "--wav")),
+            "--list-algorithms" => {
+                list_algorithms();
+                process::exit(0);
+            }
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => usage_error(&format!("Unknown option {}", arg)),
+        }
+    }
+    options
+}
+
+// Asks for sound preference
+fn ask_sound() -> bool {
     let mut sound_choice = String::new();
     println!("Do you want sound? (y/n)");
     io::stdin()
         .read_line(&mut sound_choice)
         .expect("Failed to read line");
-    let use_sound = sound_choice.trim().eq_ignore_ascii_case("y");
+    sound_choice.trim().eq_ignore_ascii_case("y")
+}
 
-    // 2. Ask for number of bars
+// Asks for number of bars
+fn ask_num_bars() -> usize {
     let mut num_bars_input = String::new();
-    let num_bars: usize;
     loop {
         println!("How many bars do you want? (e.g., 50-400, max {})", WIDTH);
         io::stdin()
             .read_line(&mut num_bars_input)
             .expect("Failed to read line");
         match num_bars_input.trim().parse::<usize>() {
-            Ok(n) if n > 0 && n <= WIDTH => {
-                num_bars = n;
-                break;
-            }
+            Ok(n) if n > 0 && n <= WIDTH => return n,
             _ => {
                 println!(
                     "Invalid input. Please enter a positive integer less than or equal to {}.",
@@ -42,61 +181,291 @@ fn main() {
             }
         }
     }
+}
 
-    // Calculate bar_width based on user's num_bars
-    let bar_width = WIDTH / num_bars;
-
-    // 3. Ask for sorting algorithm choice
-    let mut algo_choice_str = String::new();
-    let algo_choice: usize;
+// Reads a menu choice between 1 and count, asking again until the input is valid
+fn ask_choice(count: usize) -> usize {
+    let mut choice_str = String::new();
     loop {
-        println!("Which sorting algorithm?");
-        println!("  1: Bubble Sort");
-        println!("  2: Selection Sort");
-        println!("  3: Insertion Sort");
-        println!("  4: Merge Sort");
-        println!("  5: Quick Sort"); // New option
         io::stdin()
-            .read_line(&mut algo_choice_str)
+            .read_line(&mut choice_str)
             .expect("Failed to read line");
-        match algo_choice_str.trim().parse::<usize>() {
-            Ok(1) => {
-                algo_choice = 1;
-                break;
-            }
-            Ok(2) => {
-                algo_choice = 2;
-                break;
-            }
-            Ok(3) => {
-                algo_choice = 3;
-                break;
-            }
-            Ok(4) => {
-                algo_choice = 4;
-                break;
-            }
-            Ok(5) => {
-                // Handle new option
-                algo_choice = 5;
-                break;
-            }
+        match choice_str.trim().parse::<usize>() {
+            Ok(n) if n >= 1 && n <= count => return n - 1,
             _ => {
-                println!("Invalid choice. Please enter 1, 2, 3, 4, or 5.");
-                algo_choice_str.clear(); // Clear the buffer for next input
+                println!("Invalid choice. Please enter a number from 1 to {}.", count);
+                choice_str.clear(); // Clear the buffer for next input
             }
         }
     }
+}
 
-    // --- Visualization Setup ---
+// Asks for sorting algorithm choice, then for the variant if the algorithm has several
+fn ask_algorithm() -> &'static Algorithm {
+    let families = algorithm_families();
+    println!("Which sorting algorithm?");
+    for (i, (family, _)) in families.iter().enumerate() {
+        println!("  {}: {}", i + 1, family);
+    }
+    let (family, indices) = &families[ask_choice(families.len())];
+    if indices.len() == 1 {
+        return &ALGORITHMS[indices[0]];
+    }
+    println!("Which variant of {}?", family);
+    for (i, &index) in indices.iter().enumerate() {
+        println!("  {}: {}", i + 1, ALGORITHMS[index].name);
+    }
+    &ALGORITHMS[indices[ask_choice(indices.len())]]
+}
+
+// Prints every algorithm with the number --algorithm takes
+fn list_algorithms() {
+    for (i, algorithm) in ALGORITHMS.iter().enumerate() {
+        println!("{:3}: {}", i + 1, algorithm.name);
+    }
+}
 
-    // 1. Initialize the array with random values
-    let mut rng = rand::rng();
+fn main() {
+    let options = parse_args();
+
+    // --- User Input Section ---
+    // Headless runs never wait for input, they fall back to defaults
+    let (use_sound, num_bars, algorithm) = if options.headless {
+        (
+            options.sound.unwrap_or(false),
+            options.num_bars.unwrap_or(HEADLESS_NUM_BARS),
+            options.algorithm.unwrap_or(&ALGORITHMS[0]),
+        )
+    } else {
+        (
+            options.sound.unwrap_or_else(ask_sound),
+            options.num_bars.unwrap_or_else(ask_num_bars),
+            options.algorithm.unwrap_or_else(ask_algorithm),
+        )
+    };
+
+    // Algorithms that would not finish with more bars get fewer
+    let num_bars = match algorithm.max_bars {
+        Some(max_bars) if num_bars > max_bars => {
+            status(
+                &options,
+                format_args!("{} is limited to {} bars.", algorithm.name, max_bars),
+            );
+            max_bars
+        }
+        _ => num_bars,
+    };
+
+    // Calculate bar_width based on user's num_bars
+    let bar_width = WIDTH / num_bars;
+
+    // Initialize the array with random values (seeded for reproducible runs)
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
     let mut array: Vec<u32> = (0..num_bars)
         .map(|_| rng.random_range(1..=(num_bars as u32))) // Values from 1 to num_bars
         .collect();
+    // The random choices of the algorithm follow from the same seed
+    let sort_seed = rng.random();
+
+    if options.headless {
+        run_headless(
+            &options, &mut array, algorithm, sort_seed, use_sound, bar_width,
+        );
+    } else {
+        run_window(
+            &options, &mut array, algorithm, sort_seed, use_sound, bar_width,
+        );
+    }
+}
+
+// A Y4M stream goes either to a file or to stdout
+type Y4mRecorder = VideoRecorder<Y4mWriter<Box<dyn Write>>>;
+
+// The exports requested on the command line, subscribed to the sort as one observer
+struct Exports {
+    gif: Option<GifRecorder<BufWriter<File>>>,
+    y4m: Option<Y4mRecorder>,
+    ppm: Option<VideoRecorder<PpmSequence>>,
+    wav: Option<WavRenderer>,
+}
+
+impl Exports {
+    fn new(options: &Options, algorithm: &Algorithm, num_bars: usize, bar_width: usize) -> Self {
+        let gif = options.gif.as_ref().map(|path| {
+            let file = BufWriter::new(File::create(path).expect("Failed to create GIF file"));
+            let mut gif = GifRecorder::new(file, num_bars, bar_width, options.gif_options)
+                .expect("Failed to write GIF file");
+            if let Some(view) = algorithm.view {
+                gif.set_view(view(num_bars));
+            }
+            gif
+        });
+        let y4m = options.y4m.as_ref().map(|path| {
+            let writer: Box<dyn Write> = if path == "-" {
+                Box::new(BufWriter::new(io::stdout().lock()))
+            } else {
+                Box::new(BufWriter::new(
+                    File::create(path).expect("Failed to create Y4M file"),
+                ))
+            };
+            let y4m = Y4mWriter::new(writer, options.video_options.fps)
+                .expect("Failed to write Y4M file");
+            let mut video = VideoRecorder::new(y4m, num_bars, bar_width, options.video_options);
+            if let Some(view) = algorithm.view {
+                video.set_view(view(num_bars));
+            }
+            video
+        });
+        let ppm = options.ppm_dir.as_ref().map(|directory| {
+            let sequence = PpmSequence::new(directory).expect("Failed to create PPM directory");
+            let mut video =
+                VideoRecorder::new(sequence, num_bars, bar_width, options.video_options);
+            if let Some(view) = algorithm.view {
+                video.set_view(view(num_bars));
+            }
+            video
+        });
+        let wav = options
+            .wav
+            .as_ref()
+            .map(|_| WavRenderer::new(options.video_options, algorithm.tone_ms));
+        Exports { gif, y4m, ppm, wav }
+    }
+
+    // Writes the end of every export
+    fn finish(self, options: &Options) {
+        if let Some(gif) = self.gif {
+            gif.finish()
+                .and_then(|mut file| file.flush())
+                .expect("Failed to write GIF file");
+        }
+        if let Some(y4m) = self.y4m {
+            y4m.finish()
+                .and_then(|y4m| y4m.into_inner())
+                .expect("Failed to write Y4M file");
+        }
+        if let Some(ppm) = self.ppm {
+            ppm.finish().expect("Failed to write PPM files");
+        }
+        if let (Some(path), Some(wav)) = (&options.wav, self.wav) {
+            let file = BufWriter::new(File::create(path).expect("Failed to create WAV file"));
+            wav.write(file).expect("Failed to write WAV file");
+        }
+    }
+}
+
+impl SortObserver for Exports {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        self.gif.on_event(array, event)?;
+        self.y4m.on_event(array, event)?;
+        self.ppm.on_event(array, event)?;
+        self.wav.on_event(array, event)
+    }
+}
+
+// Prints a progress message; it goes to stderr while stdout carries the Y4M stream
+fn status(options: &Options, message: std::fmt::Arguments) {
+    if options.y4m.as_deref() == Some("-") {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+// Sorts against an in-memory framebuffer and a null audio sink, then prints statistics
+fn run_headless(
+    options: &Options,
+    array: &mut [u32],
+    algorithm: &Algorithm,
+    sort_seed: u64,
+    use_sound: bool,
+    bar_width: usize,
+) {
+    let num_bars = array.len();
+    let mut recorder = HeadlessRecorder::new(num_bars, bar_width);
+    if let Some(view) = algorithm.view {
+        recorder.set_view(view(num_bars));
+    }
+    let mut stats = SortStats::default();
+    let mut tones = use_sound.then(|| ToneObserver::new(NullAudio::default(), algorithm.tone_ms));
+    let mut trace = options.trace.as_ref().map(|path| {
+        let file = File::create(path).expect("Failed to create trace file");
+        TraceWriter::new(BufWriter::new(file), array)
+    });
+    let mut exports = Exports::new(options, algorithm, num_bars, bar_width);
 
-    // 2. Create a minifb window
+    status(
+        options,
+        format_args!("Starting {} (headless)...", algorithm.name),
+    );
+    let start = Instant::now();
+    let result = (algorithm.sort)(
+        array,
+        sort_seed,
+        &mut (
+            &mut recorder,
+            (&mut stats, (&mut trace, (&mut tones, &mut exports))),
+        ),
+    );
+    let elapsed = start.elapsed();
+    if result.is_break() {
+        status(options, format_args!("Sorting aborted."));
+    } else if stats.gave_up {
+        status(
+            options,
+            format_args!(
+                "{} gave up after {} operations.",
+                algorithm.name, OPERATION_BUDGET
+            ),
+        );
+    }
+
+    if let Some(trace) = trace {
+        trace.finish().expect("Failed to write trace file");
+    }
+    exports.finish(options);
+    if let Some(path) = &options.frame {
+        let mut file = BufWriter::new(File::create(path).expect("Failed to create frame file"));
+        write_ppm(&mut file, recorder.buffer()).expect("Failed to write frame file");
+    }
+
+    status(options, format_args!("Algorithm:   {}", algorithm.name));
+    status(options, format_args!("Bars:        {}", num_bars));
+    status(options, format_args!("Comparisons: {}", stats.comparisons));
+    status(options, format_args!("Swaps:       {}", stats.swaps));
+    status(options, format_args!("Reads:       {}", stats.reads));
+    status(options, format_args!("Writes:      {}", stats.writes));
+    status(options, format_args!("Aux writes:  {}", stats.aux_writes));
+    status(options, format_args!("Frames:      {}", recorder.frames));
+    if let Some(tones) = tones {
+        status(
+            options,
+            format_args!(
+                "Tones:       {} ({} ms of audio)",
+                tones.output.tones, tones.output.duration_ms
+            ),
+        );
+    }
+    status(options, format_args!("Elapsed:     {:.3?}", elapsed));
+}
+
+// Sorts in a minifb window with optional live audio
+fn run_window(
+    options: &Options,
+    array: &mut [u32],
+    algorithm: &Algorithm,
+    sort_seed: u64,
+    use_sound: bool,
+    bar_width: usize,
+) {
+    let num_bars = array.len();
+
+    // --- Visualization Setup ---
+
+    // 1. Create a minifb window
     let mut window = Window::new(
         "Sorting Algorithm Visualizer - Press ESC to exit", // Window title
         WIDTH,                                              // Window width
@@ -108,96 +477,55 @@ fn main() {
         panic!("{}", e);
     });
 
-    // Limit update rate to 60 FPS (optional, but good for smooth animation)
-    // window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
-
-    // 3. Create the pixel buffer
-    let mut buffer: Vec<u32> = vec![0; WIDTH * HEIGHT];
-
-    // 4. Initialize audio output (only if sound is enabled)
-    let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-    let sink = Sink::try_new(&stream_handle).unwrap();
-
-    // 5. Initial draw of the unsorted array
-    draw_bars(&array, &mut buffer, None, None, None, bar_width);
-    window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
-
-    // 6. Run the visualized sorting algorithm based on user choice
-    match algo_choice {
-        1 => {
-            println!("Starting Bubble Sort visualization...");
-            bubble::bubble_sort_visualized(
-                &mut array,
-                &mut window,
-                &mut buffer,
-                &sink,
-                use_sound,
-                bar_width,
-                num_bars,
-            );
-        }
-        2 => {
-            println!("Starting Selection Sort visualization...");
-            selection::selection_sort_visualized(
-                &mut array,
-                &mut window,
-                &mut buffer,
-                &sink,
-                use_sound,
-                bar_width,
-                num_bars,
-            );
-        }
-        3 => {
-            println!("Starting Insertion Sort visualization...");
-            insertion::insertion_sort_visualized(
-                &mut array,
-                &mut window,
-                &mut buffer,
-                &sink,
-                use_sound,
-                bar_width,
-                num_bars,
-            );
-        }
-        4 => {
-            println!("Starting Merge Sort visualization...");
-            sorting_algorithm_visualizer_rs::merge::merge_sort_visualized(
-                &mut array,
-                &mut window,
-                &mut buffer,
-                &sink,
-                use_sound,
-                bar_width,
-                num_bars,
-            );
-        }
-        5 => {
-            // New Quick Sort case
-            println!("Starting Quick Sort visualization...");
-            quick::quick_sort_visualized(
-                &mut array,
-                &mut window,
-                &mut buffer,
-                &sink,
-                use_sound,
-                bar_width,
-                num_bars,
-            );
-        }
-        _ => { /* Should not happen due to loop, but good for completeness */ }
-    }
-    println!("Sorting visualization finished.");
+    // Limit update rate to 60 FPS (the sort itself runs at its own speed, see --speed)
+    window.set_target_fps(60);
 
-    // 7. Keep the window open until ESC is pressed
-    // Draw the final sorted state (all green)
-    draw_bars(&array, &mut buffer, None, None, Some(0), bar_width);
-    window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
+    // 2. Initial draw of the unsorted array
+    let ops_per_second = options.ops_per_second.unwrap_or(DEFAULT_OPS_PER_SECOND);
+    let mut display = WindowDisplay::new(&mut window, algorithm, array, bar_width, ops_per_second);
+    display.show(array);
 
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        // Continue to update the window to keep it responsive
-        // and allow user to close it with ESC.
-        // No need to redraw bars as the sort is complete.
-        window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
+    // 3. Run the chosen sorting algorithm, with the window (and audio) subscribed to its events
+    status(
+        options,
+        format_args!("Starting {} visualization...", algorithm.name),
+    );
+    // The audio device is only opened when sound is enabled
+    let audio = use_sound.then(|| {
+        let (stream, stream_handle) = OutputStream::try_default().unwrap();
+        let sink = Sink::try_new(&stream_handle).unwrap();
+        (stream, sink)
+    });
+    let mut tones = audio
+        .as_ref()
+        .map(|(_stream, sink)| ToneObserver::new(ToneMixer::new(sink), algorithm.tone_ms));
+    let mut exports = Exports::new(options, algorithm, num_bars, bar_width);
+    // The statistics tell a finished sort from one that gave up
+    let mut stats = SortStats::default();
+    let result = (algorithm.sort)(
+        array,
+        sort_seed,
+        &mut (&mut display, (&mut stats, (&mut tones, &mut exports))),
+    );
+    exports.finish(options);
+    if result.is_break() {
+        // ESC or closing the window aborted the sort
+        status(options, format_args!("Sorting visualization aborted."));
+        return;
+    }
+    if stats.gave_up {
+        status(
+            options,
+            format_args!(
+                "{} gave up after {} operations.",
+                algorithm.name, OPERATION_BUDGET
+            ),
+        );
+    } else {
+        status(options, format_args!("Sorting visualization finished."));
     }
+
+    // 4. Keep the window open until ESC is pressed
+    // The final state was drawn when the algorithm finished (all green if it sorted the array)
+    display.wait_for_exit(array);
 }
@@ -1,24 +1,14 @@
-use crate::{draw_bars, play_tone};
-use minifb::Window;
-use rodio::Sink;
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
 
-// Define constants for the window resolution
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1080;
-
-#[allow(clippy::too_many_arguments)]
+// Merges the two sorted halves array[left..=mid] and array[mid+1..=right]
 pub fn merge(
     array: &mut [u32],
     left: usize,
     mid: usize,
     right: usize,
-    window: &mut Window,
-    buffer: &mut [u32],
-    sink: &Sink,
-    use_sound: bool,
-    bar_width: usize,
-    num_bars: usize,
-) {
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
     let n1 = mid - left + 1;
     let n2 = right - mid;
 
@@ -27,9 +17,16 @@ pub fn merge(
     #[allow(non_snake_case)]
     let mut R = vec![0; n2];
 
-    L[..n1].copy_from_slice(&array[left..left + n1]);
+    // Copy both halves into the auxiliary buffers L[] (id 0) and R[] (id 1)
+    observer.on_event(array, SortEvent::AuxBuffer(0, n1))?;
+    observer.on_event(array, SortEvent::AuxBuffer(1, n2))?;
+    for i in 0..n1 {
+        L[i] = array[left + i];
+        observer.on_event(array, SortEvent::AuxWrite(0, i, L[i]))?;
+    }
     for j in 0..n2 {
         R[j] = array[mid + 1 + j];
+        observer.on_event(array, SortEvent::AuxWrite(1, j, R[j]))?;
     }
 
     let mut i = 0; // Initial index of first subarray
@@ -37,115 +34,253 @@ pub fn merge(
     let mut k = left; // Initial index of merged subarray
 
     while i < n1 && j < n2 {
-        if use_sound {
-            // Play a tone for comparison during merge
-            play_tone(sink, L[i].min(R[j]), 5, num_bars); // Shorter tone for faster merge
-        }
+        // L[i] is only in its buffer, R[j] is still in the array at mid + 1 + j
+        observer.on_event(array, SortEvent::AuxCompare(0, i, L[i], mid + 1 + j))?;
 
+        // Report the element being placed, then take it out of its buffer
         if L[i] <= R[j] {
             array[k] = L[i];
+            observer.on_event(array, SortEvent::Write(k, array[k]))?;
+            observer.on_event(array, SortEvent::AuxRead(0, i))?;
             i += 1;
         } else {
             array[k] = R[j];
+            observer.on_event(array, SortEvent::Write(k, array[k]))?;
+            observer.on_event(array, SortEvent::AuxRead(1, j))?;
             j += 1;
         }
-        // Visualize the element being placed
-        draw_bars(array, buffer, Some(k), None, None, bar_width);
-        window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
         k += 1;
     }
 
     // Copy the remaining elements of L[], if any
     while i < n1 {
-        if use_sound {
-            play_tone(sink, L[i], 5, num_bars);
-        }
         array[k] = L[i];
-        // Visualize the element being placed
-        draw_bars(array, buffer, Some(k), None, None, bar_width);
-        window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
+        observer.on_event(array, SortEvent::Write(k, array[k]))?;
+        observer.on_event(array, SortEvent::AuxRead(0, i))?;
         i += 1;
         k += 1;
     }
 
     // Copy the remaining elements of R[], if any
     while j < n2 {
-        if use_sound {
-            play_tone(sink, R[j], 5, num_bars);
-        }
         array[k] = R[j];
-        // Visualize the element being placed
-        draw_bars(array, buffer, Some(k), None, None, bar_width);
-        window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
+        observer.on_event(array, SortEvent::Write(k, array[k]))?;
+        observer.on_event(array, SortEvent::AuxRead(1, j))?;
         j += 1;
         k += 1;
     }
+    ControlFlow::Continue(())
 }
 
-// Helper function for Merge Sort
-
-// Recursive function for Merge Sort visualization
-#[allow(clippy::too_many_arguments)]
+// Recursive function for Merge Sort
 pub fn merge_sort_recursive(
     array: &mut [u32],
     left: usize,
     right: usize,
-    window: &mut Window,
-    buffer: &mut [u32],
-    sink: &Sink,
-    use_sound: bool,
-    bar_width: usize,
-    num_bars: usize,
-) {
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
     if left < right {
         let mid = left + (right - left) / 2; // Avoid overflow for large left and right
 
         // Recursively sort first and second halves
-        merge_sort_recursive(
-            array, left, mid, window, buffer, sink, use_sound, bar_width, num_bars,
-        );
-        merge_sort_recursive(
-            array,
-            mid + 1,
-            right,
-            window,
-            buffer,
-            sink,
-            use_sound,
-            bar_width,
-            num_bars,
-        );
+        merge_sort_recursive(array, left, mid, observer)?;
+        merge_sort_recursive(array, mid + 1, right, observer)?;
 
         // Merge the sorted halves
-        merge(
-            array, left, mid, right, window, buffer, sink, use_sound, bar_width, num_bars,
-        );
+        merge(array, left, mid, right, observer)?;
     }
+    ControlFlow::Continue(())
 }
 
-// Wrapper for Merge Sort visualization
+// Wrapper for Merge Sort, reporting every step to the observer
 pub fn merge_sort_visualized(
     array: &mut [u32],
-    window: &mut Window,
-    buffer: &mut [u32],
-    sink: &Sink,
-    use_sound: bool,
-    bar_width: usize,
-    num_bars: usize,
-) {
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
     let n = array.len();
-    merge_sort_recursive(
-        array,
-        0,
-        n - 1,
-        window,
-        buffer,
-        sink,
-        use_sound,
-        bar_width,
-        num_bars,
-    );
-    // After sorting, draw the final sorted state in green
-    draw_bars(array, buffer, None, None, Some(0), bar_width);
-    window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
+    if n > 0 {
+        merge_sort_recursive(array, 0, n - 1, observer)?;
+    }
+    // After sorting, every element is in its final position
+    observer.on_event(array, SortEvent::Done)
+}
+
+// Implements bottom-up Merge Sort, reporting every step to the observer
+// Merges neighbouring runs of width 1, 2, 4, ... in passes over the array, without recursion
+pub fn merge_sort_bottom_up_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    let mut width = 1;
+    while width < n {
+        let mut left = 0;
+        // Merge array[left..left + width] with the (possibly shorter) run after it
+        while left + width < n {
+            let mid = left + width - 1;
+            let right = (left + 2 * width - 1).min(n - 1);
+            merge(array, left, mid, right, observer)?;
+            left += 2 * width;
+        }
+        width *= 2;
+    }
+    observer.on_event(array, SortEvent::Done)
+}
+
+// Implements natural Merge Sort, reporting every step to the observer
+// Splits the array into the ascending runs it already contains and merges neighbouring runs
+// in passes, so presorted parts of the array are not split up
+pub fn natural_merge_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    // Starts of the runs, followed by the end of the array
+    let mut bounds = vec![0];
+    for i in 1..n {
+        observer.on_event(array, SortEvent::Compare(i - 1, i))?;
+        if array[i] < array[i - 1] {
+            bounds.push(i);
+        }
+    }
+    bounds.push(n);
+
+    while bounds.len() > 2 {
+        let mut merged = vec![0];
+        // bounds[k], bounds[k + 1] and bounds[k + 2] are the start of a run, the start of the
+        // next one and the end of that one
+        let mut k = 0;
+        while k + 2 < bounds.len() {
+            merge(
+                array,
+                bounds[k],
+                bounds[k + 1] - 1,
+                bounds[k + 2] - 1,
+                observer,
+            )?;
+            merged.push(bounds[k + 2]);
+            k += 2;
+        }
+        // An odd run out is merged in the next pass
+        if merged.last() != Some(&n) {
+            merged.push(n);
+        }
+        bounds = merged;
+    }
+    observer.on_event(array, SortEvent::Done)
+}
+
+// Reverses array[low..high] with swaps
+fn reverse(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let (mut i, mut j) = (low, high);
+    while i + 1 < j {
+        j -= 1;
+        array.swap(i, j);
+        observer.on_event(array, SortEvent::Swap(i, j))?;
+        i += 1;
+    }
+    ControlFlow::Continue(())
+}
+
+// Swaps the neighbouring ranges array[low..mid] and array[mid..high] with three reversals
+pub fn rotate(
+    array: &mut [u32],
+    low: usize,
+    mid: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    reverse(array, low, mid, observer)?;
+    reverse(array, mid, high, observer)?;
+    reverse(array, low, high, observer)
+}
+
+// Merges the sorted runs array[low..mid] and array[mid..high] without a buffer
+// Splits the longer run in the middle, finds where its middle element goes in the other run
+// (binary search), rotates the two parts in between past each other and merges both sides
+pub fn merge_in_place(
+    array: &mut [u32],
+    low: usize,
+    mid: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    if low == mid || mid == high {
+        return ControlFlow::Continue(());
+    }
+    if high - low == 2 {
+        observer.on_event(array, SortEvent::Compare(low, mid))?;
+        if array[mid] < array[low] {
+            array.swap(low, mid);
+            observer.on_event(array, SortEvent::Swap(low, mid))?;
+        }
+        return ControlFlow::Continue(());
+    }
+    let (cut1, cut2);
+    if mid - low > high - mid {
+        // The first element of the right run not less than the middle of the left run
+        cut1 = low + (mid - low) / 2;
+        let (mut first, mut last) = (mid, high);
+        while first < last {
+            let probe = first + (last - first) / 2;
+            observer.on_event(array, SortEvent::Compare(probe, cut1))?;
+            if array[probe] < array[cut1] {
+                first = probe + 1;
+            } else {
+                last = probe;
+            }
+        }
+        cut2 = first;
+    } else {
+        // The first element of the left run greater than the middle of the right run
+        cut2 = mid + (high - mid) / 2;
+        let (mut first, mut last) = (low, mid);
+        while first < last {
+            let probe = first + (last - first) / 2;
+            observer.on_event(array, SortEvent::Compare(probe, cut2))?;
+            if array[probe] <= array[cut2] {
+                first = probe + 1;
+            } else {
+                last = probe;
+            }
+        }
+        cut1 = first;
+    }
+    // Rotate array[cut1..mid] and array[mid..cut2] past each other
+    rotate(array, cut1, mid, cut2, observer)?;
+    let new_mid = cut1 + (cut2 - mid);
+    merge_in_place(array, low, cut1, new_mid, observer)?;
+    merge_in_place(array, new_mid, cut2, high, observer)
+}
+
+// Recursive function for in-place Merge Sort, sorting array[low..high]
+fn merge_sort_in_place_recursive(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    if high - low < 2 {
+        return ControlFlow::Continue(());
+    }
+    let mid = low + (high - low) / 2;
+    merge_sort_in_place_recursive(array, low, mid, observer)?;
+    merge_sort_in_place_recursive(array, mid, high, observer)?;
+    merge_in_place(array, low, mid, high, observer)
+}
+
+// Implements in-place Merge Sort, reporting every step to the observer
+// Top-down like merge_sort_visualized, but merges by rotations instead of buffers:
+// no extra memory, at the price of O(n log² n) swaps
+pub fn merge_sort_in_place_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    merge_sort_in_place_recursive(array, 0, array.len(), observer)?;
+    observer.on_event(array, SortEvent::Done)
 }
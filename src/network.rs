@@ -0,0 +1,97 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// The layers of a sorting network, each a list of comparators (i, j) with i < j
+// The comparators of a layer touch disjoint elements, so they could all run at once
+pub type Network = Vec<Vec<(usize, usize)>>;
+
+// The comparators of Bitonic Sort for n elements
+// Each merge starts with a layer comparing mirrored positions, followed by half-cleaners,
+// so every comparator sorts ascending. For n that is not a power of two the network is that
+// of the next power of two with the comparators beyond the array left out: they would only
+// compare against padding greater than every element, which never moves anything.
+pub fn bitonic_network(n: usize) -> Network {
+    let mut layers = Vec::new();
+    let mut layer = |distance: &dyn Fn(usize) -> usize| {
+        let comparators: Vec<(usize, usize)> = (0..n)
+            .map(|i| (i, distance(i)))
+            .filter(|&(i, j)| i < j && j < n)
+            .collect();
+        if !comparators.is_empty() {
+            layers.push(comparators);
+        }
+    };
+    let mut size = 2; // Length of the sorted sequences the layers build
+    while size / 2 < n {
+        layer(&|i| i ^ (size - 1));
+        let mut step = size / 4;
+        while step > 0 {
+            layer(&|i| i ^ step);
+            step /= 2;
+        }
+        size *= 2;
+    }
+    layers
+}
+
+// The comparators of Batcher's odd-even merge sort for n elements
+// Merges sorted sequences of length p pairwise, comparing elements k apart for k = p, p / 2, ..., 1
+// and keeping only the comparators within the same pair. Works for any n.
+pub fn odd_even_merge_network(n: usize) -> Network {
+    let mut layers = Vec::new();
+    let mut p = 1;
+    while p < n {
+        let mut k = p;
+        while k > 0 {
+            let mut comparators = Vec::new();
+            for j in (k % p..n - k).step_by(2 * k) {
+                for i in j..j + k.min(n - j - k) {
+                    if i / (2 * p) == (i + k) / (2 * p) {
+                        comparators.push((i, i + k));
+                    }
+                }
+            }
+            if !comparators.is_empty() {
+                layers.push(comparators);
+            }
+            k /= 2;
+        }
+        p *= 2;
+    }
+    layers
+}
+
+// Runs the comparators of a sorting network layer by layer, reporting every step to the observer
+pub fn network_sort(
+    array: &mut [u32],
+    network: &Network,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    for (index, layer) in network.iter().enumerate() {
+        observer.on_event(array, SortEvent::Layer(index))?;
+        for &(i, j) in layer {
+            observer.on_event(array, SortEvent::Compare(i, j))?;
+            if array[i] > array[j] {
+                array.swap(i, j);
+                observer.on_event(array, SortEvent::Swap(i, j))?;
+            }
+        }
+    }
+    observer.on_event(array, SortEvent::Done)
+}
+
+// Implements Bitonic Sort, reporting every step to the observer
+pub fn bitonic_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    network_sort(array, &bitonic_network(array.len()), observer)
+}
+
+// Implements Batcher's odd-even merge sort, reporting every step to the observer
+pub fn odd_even_merge_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    network_sort(array, &odd_even_merge_network(array.len()), observer)
+}
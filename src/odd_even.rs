@@ -0,0 +1,30 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Implements Odd-Even Transposition Sort, reporting every step to the observer
+// Alternates between comparing the pairs (1, 2), (3, 4), ... and the pairs (0, 1), (2, 3), ...
+// The pairs of a phase are independent of each other (the sort is made for parallel hardware)
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the comparisons and swaps as events
+pub fn odd_even_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    let mut sorted = false;
+    while !sorted {
+        sorted = true;
+        // Odd phase, then even phase
+        for first in [1, 0] {
+            for i in (first..n.saturating_sub(1)).step_by(2) {
+                observer.on_event(array, SortEvent::Compare(i, i + 1))?;
+                if array[i] > array[i + 1] {
+                    array.swap(i, i + 1);
+                    observer.on_event(array, SortEvent::Swap(i, i + 1))?;
+                    sorted = false;
+                }
+            }
+        }
+    }
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,122 @@
+use crate::draw::darken_rect;
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::stats::SortStats;
+use crate::WIDTH;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+const OVERLAY_SCALE: usize = 3; // Screen pixels per font pixel
+const OVERLAY_MARGIN: usize = 12; // Distance from the window corner and padding around the text
+const OVERLAY_LINE_HEIGHT: usize = (GLYPH_HEIGHT + 3) * OVERLAY_SCALE;
+const OVERLAY_TEXT: u32 = 0xFFFFFF;
+
+// Statistics shown over the bars while an algorithm runs
+// Counts the operations from the event stream and measures the wall time from the
+// first event until the sort is done, leaving out the time the sort was paused.
+pub struct StatsOverlay {
+    name: &'static str,
+    num_bars: usize,
+    pub stats: SortStats,
+    started: Option<Instant>,
+    elapsed: Option<Duration>, // Set once the sort is done
+    paused: Duration,          // Time spent paused before the current pause
+    paused_since: Option<Instant>,
+}
+
+impl StatsOverlay {
+    // name: The name of the algorithm
+    // num_bars: The number of elements being sorted
+    pub fn new(name: &'static str, num_bars: usize) -> Self {
+        StatsOverlay {
+            name,
+            num_bars,
+            stats: SortStats::default(),
+            started: None,
+            elapsed: None,
+            paused: Duration::ZERO,
+            paused_since: None,
+        }
+    }
+
+    // Stops the clock while the sort is paused (or its history is being reviewed)
+    pub fn pause(&mut self) {
+        if self.started.is_some() && self.paused_since.is_none() {
+            self.paused_since = Some(Instant::now());
+        }
+    }
+
+    // Starts the clock again after a pause
+    pub fn resume(&mut self) {
+        if let Some(since) = self.paused_since.take() {
+            self.paused += since.elapsed();
+        }
+    }
+
+    // Wall time of the sort so far, without the pauses
+    pub fn elapsed(&self) -> Duration {
+        match (self.elapsed, self.started) {
+            (Some(elapsed), _) => elapsed,
+            (None, Some(started)) => {
+                let now = self.paused_since.unwrap_or_else(Instant::now);
+                (now - started).saturating_sub(self.paused)
+            }
+            (None, None) => Duration::ZERO,
+        }
+    }
+
+    // The lines of text shown by the overlay
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            self.name.to_string(),
+            format!("Bars: {}", self.num_bars),
+            format!("Comparisons: {}", self.stats.comparisons),
+            format!("Swaps: {}", self.stats.swaps),
+            format!("Array reads: {}", self.stats.reads),
+            format!("Array writes: {}", self.stats.writes),
+            format!("Aux writes: {}", self.stats.aux_writes),
+            format!("Time: {:.3} s", self.elapsed().as_secs_f64()),
+        ]
+    }
+
+    // Draws the overlay into the top left corner of the pixel buffer
+    // The bars behind the text are darkened to keep it readable
+    pub fn draw(&self, buffer: &mut [u32]) {
+        let lines = self.lines();
+        let width = lines
+            .iter()
+            .map(|line| text_width(line, OVERLAY_SCALE))
+            .max()
+            .unwrap_or(0);
+        let right = (OVERLAY_MARGIN * 2 + width).min(WIDTH);
+        let bottom = OVERLAY_MARGIN * 2 + lines.len() * OVERLAY_LINE_HEIGHT;
+        darken_rect(
+            buffer,
+            OVERLAY_MARGIN / 2,
+            OVERLAY_MARGIN / 2,
+            right - OVERLAY_MARGIN / 2,
+            bottom - OVERLAY_MARGIN / 2,
+        );
+        for (index, line) in lines.iter().enumerate() {
+            let y = OVERLAY_MARGIN * 3 / 2 + index * OVERLAY_LINE_HEIGHT;
+            draw_text(
+                buffer,
+                OVERLAY_MARGIN * 3 / 2,
+                y,
+                line,
+                OVERLAY_SCALE,
+                OVERLAY_TEXT,
+            );
+        }
+    }
+}
+
+impl SortObserver for StatsOverlay {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        self.started.get_or_insert_with(Instant::now);
+        if event == SortEvent::Done {
+            self.elapsed = Some(self.elapsed());
+        }
+        self.stats.on_event(array, event)
+    }
+}
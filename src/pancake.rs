@@ -0,0 +1,43 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Reverses the prefix array[..len] with swaps, the only way Pancake Sort moves elements
+fn flip(array: &mut [u32], len: usize, observer: &mut dyn SortObserver) -> ControlFlow<()> {
+    observer.on_event(array, SortEvent::Flip(len))?;
+    let (mut i, mut j) = (0, len);
+    while i + 1 < j {
+        j -= 1;
+        array.swap(i, j);
+        observer.on_event(array, SortEvent::Swap(i, j))?;
+        i += 1;
+    }
+    ControlFlow::Continue(())
+}
+
+// Implements Pancake Sort, reporting every step to the observer
+// Elements are only moved by flips (reversals of a prefix, like a spatula turning over the top
+// of a stack of pancakes): the largest unsorted element is flipped to the front, then the whole
+// unsorted prefix is flipped to bring it to the end. At most 2n - 3 flips.
+pub fn pancake_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    for size in (2..=array.len()).rev() {
+        // Find the largest element of the unsorted prefix array[..size]
+        let mut max_idx = 0;
+        for i in 1..size {
+            observer.on_event(array, SortEvent::Compare(i, max_idx))?;
+            if array[i] > array[max_idx] {
+                max_idx = i;
+            }
+        }
+        if max_idx != size - 1 {
+            if max_idx > 0 {
+                flip(array, max_idx + 1, observer)?;
+            }
+            flip(array, size, observer)?;
+        }
+        observer.on_event(array, SortEvent::MarkSorted(size - 1))?;
+    }
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,350 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::heap::heap_sort_range;
+use crate::insertion::{insert, insertion_sort_range};
+use std::ops::ControlFlow;
+
+// The constants of the pattern-defeating quicksort behind slice::sort_unstable
+const MAX_INSERTION: usize = 20; // Ranges up to this length are Insertion Sorted
+const SHORTEST_MEDIAN_OF_MEDIANS: usize = 50; // From this length on, the pivot is a ninther
+const MAX_SWAPS: usize = 4 * 3; // All pivot candidates out of order, the range is likely descending
+const MAX_STEPS: usize = 5; // Adjacent out of order pairs fixed by the partial insertion sort
+const SHORTEST_SHIFTING: usize = 50; // Shorter ranges are not worth a partial insertion sort
+
+// Orders the indices a and b by the elements they point to (the elements are not moved)
+fn sort2(
+    array: &[u32],
+    a: &mut usize,
+    b: &mut usize,
+    swaps: &mut usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    observer.on_event(array, SortEvent::Compare(*a, *b))?;
+    if array[*b] < array[*a] {
+        std::mem::swap(a, b);
+        *swaps += 1;
+    }
+    ControlFlow::Continue(())
+}
+
+// Orders the indices a, b and c by the elements they point to, b ends up at the median
+fn sort3(
+    array: &[u32],
+    a: &mut usize,
+    b: &mut usize,
+    c: &mut usize,
+    swaps: &mut usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    sort2(array, a, b, swaps, observer)?;
+    sort2(array, b, c, swaps, observer)?;
+    sort2(array, a, b, swaps, observer)
+}
+
+// Chooses the pivot of array[low..high] (longer than MAX_INSERTION)
+// Returns its index and whether the range looks already sorted
+fn choose_pivot(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), (usize, bool)> {
+    let len = high - low;
+    let mut a = low + len / 4;
+    let mut b = low + len / 4 * 2;
+    let mut c = low + len / 4 * 3;
+    let mut swaps = 0;
+    if len >= SHORTEST_MEDIAN_OF_MEDIANS {
+        // Tukey's ninther: the median of the medians of the neighbourhoods of a, b and c
+        observer.on_event(array, SortEvent::Phase("Ninther"))?;
+        for middle in [&mut a, &mut b, &mut c] {
+            let (mut before, mut after) = (*middle - 1, *middle + 1);
+            sort3(array, &mut before, middle, &mut after, &mut swaps, observer)?;
+        }
+    } else {
+        observer.on_event(array, SortEvent::Phase("Median of 3"))?;
+    }
+    sort3(array, &mut a, &mut b, &mut c, &mut swaps, observer)?;
+
+    if swaps < MAX_SWAPS {
+        return ControlFlow::Continue((b, swaps == 0));
+    }
+    // Every candidate was out of order, the range is probably descending: reverse it
+    observer.on_event(array, SortEvent::Phase("Reversed"))?;
+    for i in 0..len / 2 {
+        array.swap(low + i, high - 1 - i);
+        observer.on_event(array, SortEvent::Swap(low + i, high - 1 - i))?;
+    }
+    ControlFlow::Continue((low + high - 1 - b, true))
+}
+
+// Moves array[i] right into the sorted run array[i + 1..high]
+fn shift_head(
+    array: &mut [u32],
+    i: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let key = array[i];
+    let mut j = i;
+    while j + 1 < high {
+        observer.on_event(array, SortEvent::Compare(j, j + 1))?;
+        if array[j + 1] >= key {
+            break;
+        }
+        array[j] = array[j + 1]; // Shift element to the left
+        observer.on_event(array, SortEvent::Write(j, array[j]))?;
+        j += 1;
+    }
+    if j != i {
+        array[j] = key;
+        observer.on_event(array, SortEvent::Write(j, key))?;
+    }
+    ControlFlow::Continue(())
+}
+
+// Tries to sort an almost sorted array[low..high] by fixing a few out of order pairs
+// Returns true if the range ended up sorted
+fn partial_insertion_sort(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), bool> {
+    let mut i = low + 1;
+    for _ in 0..MAX_STEPS {
+        // Find the next pair of adjacent out of order elements
+        while i < high {
+            observer.on_event(array, SortEvent::Compare(i - 1, i))?;
+            if array[i] < array[i - 1] {
+                break;
+            }
+            i += 1;
+        }
+        if i == high {
+            return ControlFlow::Continue(true);
+        }
+        if high - low < SHORTEST_SHIFTING {
+            return ControlFlow::Continue(false);
+        }
+        // Swap the pair and shift both elements into place
+        array.swap(i - 1, i);
+        observer.on_event(array, SortEvent::Swap(i - 1, i))?;
+        insert(array, low, i - 1, observer)?;
+        shift_head(array, i, high, observer)?;
+    }
+    ControlFlow::Continue(false)
+}
+
+// Scatters a few elements of array[low..high] after an unbalanced partition, so the next pivot
+// is unlikely to be as bad (the positions come from a xorshift generator seeded with the length)
+fn break_patterns(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let len = high - low;
+    if len < 8 {
+        return ControlFlow::Continue(());
+    }
+    let mut random = len as u64;
+    let modulus = len.next_power_of_two();
+    let pos = low + len / 4 * 2;
+    for i in 0..3 {
+        random ^= random << 13;
+        random ^= random >> 7;
+        random ^= random << 17;
+        let mut other = random as usize & (modulus - 1);
+        if other >= len {
+            other -= len;
+        }
+        array.swap(pos - 1 + i, low + other);
+        observer.on_event(array, SortEvent::Swap(pos - 1 + i, low + other))?;
+    }
+    ControlFlow::Continue(())
+}
+
+// Moves l right past the elements less than the pivot (at low) and r left past the others
+fn scan(
+    array: &[u32],
+    low: usize,
+    l: &mut usize,
+    r: &mut usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    while *l < *r {
+        observer.on_event(array, SortEvent::Compare(*l, low))?;
+        if array[*l] >= array[low] {
+            break;
+        }
+        *l += 1;
+    }
+    while *l < *r {
+        observer.on_event(array, SortEvent::Compare(*r - 1, low))?;
+        if array[*r - 1] < array[low] {
+            break;
+        }
+        *r -= 1;
+    }
+    ControlFlow::Continue(())
+}
+
+// Partitions array[low..high] into the elements less than the pivot and the others
+// (slice::sort_unstable swaps them in blocks to avoid branches, here one pair at a time)
+// Returns the final index of the pivot and whether the range was already partitioned
+fn partition(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    pivot: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), (usize, bool)> {
+    array.swap(low, pivot);
+    observer.on_event(array, SortEvent::Swap(low, pivot))?;
+    observer.on_event(array, SortEvent::Pivot(low))?;
+    let (mut l, mut r) = (low + 1, high);
+    scan(array, low, &mut l, &mut r, observer)?;
+    let was_partitioned = l >= r;
+    while l < r {
+        r -= 1;
+        array.swap(l, r);
+        observer.on_event(array, SortEvent::Swap(l, r))?;
+        l += 1;
+        scan(array, low, &mut l, &mut r, observer)?;
+    }
+    // Put the pivot between the two parts
+    let mid = l - 1;
+    array.swap(low, mid);
+    observer.on_event(array, SortEvent::Swap(low, mid))?;
+    ControlFlow::Continue((mid, was_partitioned))
+}
+
+// Moves the elements equal to the pivot to the start of array[low..high]
+// Only called when no element of the range is less than the pivot
+// Returns the index one past the equal elements
+fn partition_equal(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    pivot: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    array.swap(low, pivot);
+    observer.on_event(array, SortEvent::Swap(low, pivot))?;
+    observer.on_event(array, SortEvent::Pivot(low))?;
+    let (mut l, mut r) = (low + 1, high);
+    loop {
+        while l < r {
+            observer.on_event(array, SortEvent::Compare(l, low))?;
+            if array[low] < array[l] {
+                break;
+            }
+            l += 1;
+        }
+        while l < r {
+            observer.on_event(array, SortEvent::Compare(r - 1, low))?;
+            if array[low] >= array[r - 1] {
+                break;
+            }
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+        r -= 1;
+        array.swap(l, r);
+        observer.on_event(array, SortEvent::Swap(l, r))?;
+        l += 1;
+    }
+    ControlFlow::Continue(l)
+}
+
+// Sorts array[low..high], recursing into the shorter part of every partition
+// pred: Index of the pivot right before the range, if any (no element of the range is less)
+// limit: Number of unbalanced partitions allowed before falling back to Heap Sort
+fn pdqsort_recursive(
+    array: &mut [u32],
+    mut low: usize,
+    mut high: usize,
+    mut pred: Option<usize>,
+    mut limit: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+    loop {
+        let len = high - low;
+        if len <= MAX_INSERTION {
+            observer.on_event(array, SortEvent::Phase("Insertion Sort"))?;
+            return insertion_sort_range(array, low, high, observer);
+        }
+        if limit == 0 {
+            observer.on_event(array, SortEvent::Phase("Heap Sort fallback"))?;
+            return heap_sort_range(array, low, high, observer);
+        }
+        if !was_balanced {
+            // The last partition was bad, shuffle some elements around
+            observer.on_event(array, SortEvent::Phase("Break patterns"))?;
+            break_patterns(array, low, high, observer)?;
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(array, low, high, observer)?;
+        // The last partition was balanced, moved nothing and the candidates were in order:
+        // the range is probably (almost) sorted already
+        if was_balanced && was_partitioned && likely_sorted {
+            observer.on_event(array, SortEvent::Phase("Partial insertion"))?;
+            if partial_insertion_sort(array, low, high, observer)? {
+                for i in low..high {
+                    observer.on_event(array, SortEvent::MarkSorted(i))?;
+                }
+                return ControlFlow::Continue(());
+            }
+        }
+
+        // A pivot equal to the previous one means the range starts with many equal elements,
+        // which are put in place at once
+        if let Some(pred) = pred {
+            observer.on_event(array, SortEvent::Compare(pred, pivot))?;
+            if array[pred] >= array[pivot] {
+                observer.on_event(array, SortEvent::Phase("Equal partition"))?;
+                let mid = partition_equal(array, low, high, pivot, observer)?;
+                for i in low..mid {
+                    observer.on_event(array, SortEvent::MarkSorted(i))?;
+                }
+                low = mid;
+                continue;
+            }
+        }
+
+        observer.on_event(array, SortEvent::Phase("Partition"))?;
+        let (mid, partitioned) = partition(array, low, high, pivot, observer)?;
+        // The pivot is now in its final position
+        observer.on_event(array, SortEvent::MarkSorted(mid))?;
+        was_balanced = (mid - low).min(high - mid) >= len / 8;
+        was_partitioned = partitioned;
+
+        // Recurse into the shorter part to keep the stack at O(log n), loop on the longer one
+        if mid - low < high - mid - 1 {
+            pdqsort_recursive(array, low, mid, pred, limit, observer)?;
+            low = mid + 1;
+            pred = Some(mid);
+        } else {
+            pdqsort_recursive(array, mid + 1, high, Some(mid), limit, observer)?;
+            high = mid;
+        }
+    }
+}
+
+// Implements a pattern-defeating quicksort (pdqsort) the way slice::sort_unstable does it,
+// reporting every step to the observer: the pivot choice (median of 3 or ninther), reversing
+// descending ranges, partial insertion sorts of almost sorted ranges, breaking patterns after
+// unbalanced partitions and the Heap Sort fallback once too many of them happened
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the comparisons, swaps, phases and sorted positions as events
+pub fn pdqsort_visualized(array: &mut [u32], observer: &mut dyn SortObserver) -> ControlFlow<()> {
+    let n = array.len();
+    let limit = (usize::BITS - n.leading_zeros()) as usize;
+    pdqsort_recursive(array, 0, n, None, limit, observer)?;
+    observer.on_event(array, SortEvent::Done)
+}
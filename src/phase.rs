@@ -0,0 +1,104 @@
+use crate::draw::darken_rect;
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::render::SortView;
+use crate::WIDTH;
+use std::ops::ControlFlow;
+
+const LABEL_SCALE: usize = 3;
+const LABEL_MARGIN: usize = 12;
+const LINE_HEIGHT: usize = (GLYPH_HEIGHT + 3) * LABEL_SCALE;
+const TEXT_COLOR: u32 = 0xFFFFFF;
+const CURRENT_COLOR: u32 = 0xFFFF00; // The phase the algorithm is in
+
+// Lists the phases a hybrid algorithm went through (e.g. the Heap Sort fallback of Introsort)
+// with how often it entered each one, highlighting the current phase
+pub struct PhaseView {
+    phases: Vec<(&'static str, usize)>, // In the order they were first entered
+    current: Option<usize>,             // Index into phases
+}
+
+impl PhaseView {
+    pub fn new() -> Self {
+        PhaseView {
+            phases: Vec::new(),
+            current: None,
+        }
+    }
+
+    // The phase the algorithm is in
+    pub fn current(&self) -> Option<&'static str> {
+        self.current.map(|index| self.phases[index].0)
+    }
+
+    // How often the algorithm entered the phase
+    pub fn count(&self, name: &str) -> usize {
+        self.phases
+            .iter()
+            .find(|(phase, _)| *phase == name)
+            .map_or(0, |(_, count)| *count)
+    }
+}
+
+impl Default for PhaseView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SortObserver for PhaseView {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        match event {
+            SortEvent::Phase(name) => {
+                let index = match self.phases.iter().position(|(phase, _)| *phase == name) {
+                    Some(index) => index,
+                    None => {
+                        self.phases.push((name, 0));
+                        self.phases.len() - 1
+                    }
+                };
+                self.phases[index].1 += 1;
+                self.current = Some(index);
+            }
+            SortEvent::Done => self.current = None,
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for PhaseView {
+    // Draws the list into the top right corner of the window
+    fn draw(&self, buffer: &mut [u32]) {
+        if self.phases.is_empty() {
+            return;
+        }
+        let lines: Vec<String> = self
+            .phases
+            .iter()
+            .map(|(name, count)| format!("{}: {}", name, count))
+            .collect();
+        let width = lines
+            .iter()
+            .map(|line| text_width(line, LABEL_SCALE))
+            .max()
+            .unwrap_or(0);
+        let x = WIDTH - width - 2 * LABEL_MARGIN;
+        darken_rect(
+            buffer,
+            x - LABEL_MARGIN,
+            LABEL_MARGIN / 2,
+            width + 2 * LABEL_MARGIN,
+            lines.len() * LINE_HEIGHT + LABEL_MARGIN,
+        );
+        for (index, line) in lines.iter().enumerate() {
+            let color = if self.current == Some(index) {
+                CURRENT_COLOR
+            } else {
+                TEXT_COLOR
+            };
+            let y = LABEL_MARGIN * 3 / 2 + index * LINE_HEIGHT;
+            draw_text(buffer, x, y, line, LABEL_SCALE, color);
+        }
+    }
+}
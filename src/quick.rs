@@ -1,142 +1,362 @@
-use crate::{draw_bars, play_tone};
-use minifb::Window;
-use rodio::Sink;
+use crate::events::{SortEvent, SortObserver};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::ops::ControlFlow;
 
-// Define constants for the window resolution
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1080;
+// How Quick Sort picks the pivot of a partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotStrategy {
+    First,
+    Last,
+    Middle,
+    Random,        // Drawn from an RNG seeded by the caller, so runs can be repeated
+    MedianOfThree, // Of the first, middle and last element
+    Ninther,       // Tukey's median of the medians of three groups of three
+}
+
+// How Quick Sort moves the elements around the pivot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionScheme {
+    Lomuto,    // One index scanning from the left, the pivot at the end
+    Hoare,     // Two indices scanning towards each other, the pivot at the start
+    ThreeWay,  // Dijkstra's Dutch national flag: less, equal and greater parts
+    DualPivot, // Yaroslavskiy: two pivots and three parts (Java's Arrays.sort)
+}
 
 // Helper function for Quick Sort partitioning
-#[allow(clippy::too_many_arguments)]
-fn partition(
+// Partitions array[low..=high] around array[high] and returns the final index of the pivot
+pub fn partition(
     array: &mut [u32],
     low: usize,
     high: usize,
-    window: &mut Window,
-    buffer: &mut [u32],
-    sink: &Sink,
-    use_sound: bool,
-    bar_width: usize,
-    num_bars: usize,
-) -> usize {
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
     let pivot = array[high]; // Choose the last element as the pivot
+    observer.on_event(array, SortEvent::Pivot(high))?;
     let mut i = low; // Index of smaller element
 
     for j in low..high {
-        if use_sound {
-            // Play tone for comparison (array[j] with pivot)
-            play_tone(sink, array[j], 5, num_bars); // Shorter tone for faster Quick Sort
-        }
-
-        // Draw elements being compared to the pivot
-        draw_bars(array, buffer, Some(j), Some(high), None, bar_width);
-        window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
+        // Compare the current element to the pivot
+        observer.on_event(array, SortEvent::Compare(j, high))?;
 
         // If current element is smaller than or equal to pivot
         if array[j] <= pivot {
             array.swap(i, j); // Swap elements
-            if use_sound {
-                // Play tone for swap
-                play_tone(sink, array[i], 5, num_bars);
-            }
-            // Draw after swap
-            draw_bars(array, buffer, Some(i), Some(j), None, bar_width);
-            window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
+            observer.on_event(array, SortEvent::Swap(i, j))?;
             i += 1;
         }
     }
     array.swap(i, high); // Place the pivot at its correct sorted position
-    if use_sound {
-        // Play tone for final pivot placement
-        play_tone(sink, array[i], 5, num_bars);
+    observer.on_event(array, SortEvent::Swap(i, high))?;
+    ControlFlow::Continue(i)
+}
+
+// Hoare partitioning of array[low..=high] around array[low]
+// Returns the final index of the pivot
+fn hoare_partition(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    observer.on_event(array, SortEvent::Pivot(low))?;
+    let pivot = array[low];
+    let mut i = low;
+    let mut j = high + 1;
+    loop {
+        // Find an element on the left that belongs to the right part
+        loop {
+            i += 1;
+            observer.on_event(array, SortEvent::Compare(i, low))?;
+            if array[i] >= pivot || i == high {
+                break;
+            }
+        }
+        // Find an element on the right that belongs to the left part
+        loop {
+            j -= 1;
+            if j == low {
+                break;
+            }
+            observer.on_event(array, SortEvent::Compare(j, low))?;
+            if array[j] <= pivot {
+                break;
+            }
+        }
+        if i >= j {
+            break;
+        }
+        array.swap(i, j);
+        observer.on_event(array, SortEvent::Swap(i, j))?;
     }
-    // Draw final pivot placement
-    draw_bars(array, buffer, Some(i), Some(high), None, bar_width);
-    window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
-    i
+    // Place the pivot between the two parts
+    array.swap(low, j);
+    observer.on_event(array, SortEvent::Swap(low, j))?;
+    ControlFlow::Continue(j)
 }
 
-// Recursive function for Quick Sort visualization
-#[allow(clippy::too_many_arguments)]
-fn quick_sort_recursive(
+// 3-way partitioning of array[low..=high] around array[low]
+// Returns the first and last index of the elements equal to the pivot
+fn three_way_partition(
     array: &mut [u32],
     low: usize,
     high: usize,
-    window: &mut Window,
-    buffer: &mut [u32],
-    sink: &Sink,
-    use_sound: bool,
-    bar_width: usize,
-    num_bars: usize,
-) {
-    if low < high {
-        let pi = partition(
-            array, low, high, window, buffer, sink, use_sound, bar_width, num_bars,
-        );
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), (usize, usize)> {
+    observer.on_event(array, SortEvent::Pivot(low))?;
+    let pivot = array[low];
+    // array[low..lt] < pivot, array[lt..i] == pivot, array[gt + 1..=high] > pivot
+    let mut lt = low;
+    let mut i = low + 1;
+    let mut gt = high;
+    while i <= gt {
+        // array[lt] always holds a copy of the pivot
+        observer.on_event(array, SortEvent::Compare(i, lt))?;
+        if array[i] < pivot {
+            array.swap(lt, i);
+            observer.on_event(array, SortEvent::Swap(lt, i))?;
+            lt += 1;
+            i += 1;
+        } else if array[i] > pivot {
+            array.swap(i, gt);
+            observer.on_event(array, SortEvent::Swap(i, gt))?;
+            gt -= 1;
+        } else {
+            i += 1;
+        }
+    }
+    ControlFlow::Continue((lt, gt))
+}
 
-        // Recursively sort elements before partition and after partition
-        if pi > 0 {
-            // Ensure pi is not 0 to prevent underflow with pi - 1
-            quick_sort_recursive(
-                array,
-                low,
-                pi - 1,
-                window,
-                buffer,
-                sink,
-                use_sound,
-                bar_width,
-                num_bars,
-            );
+// Dual-pivot partitioning of array[low..=high] around array[low] and array[high]
+// Returns the final indices of the two pivots
+fn dual_pivot_partition(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), (usize, usize)> {
+    // The smaller pivot goes first
+    observer.on_event(array, SortEvent::Compare(low, high))?;
+    if array[low] > array[high] {
+        array.swap(low, high);
+        observer.on_event(array, SortEvent::Swap(low, high))?;
+    }
+    observer.on_event(array, SortEvent::Pivot(low))?;
+    observer.on_event(array, SortEvent::Pivot(high))?;
+    let (p, q) = (array[low], array[high]);
+    // array[low + 1..lt] < p, array[lt..k] between p and q, array[gt + 1..high] > q
+    let mut lt = low + 1;
+    let mut gt = high - 1;
+    let mut k = low + 1;
+    while k <= gt {
+        observer.on_event(array, SortEvent::Compare(k, low))?;
+        if array[k] < p {
+            array.swap(k, lt);
+            observer.on_event(array, SortEvent::Swap(k, lt))?;
+            lt += 1;
+        } else {
+            observer.on_event(array, SortEvent::Compare(k, high))?;
+            if array[k] > q {
+                // Skip the elements at the right end that are already greater than q
+                while k < gt {
+                    observer.on_event(array, SortEvent::Compare(gt, high))?;
+                    if array[gt] <= q {
+                        break;
+                    }
+                    gt -= 1;
+                }
+                array.swap(k, gt);
+                observer.on_event(array, SortEvent::Swap(k, gt))?;
+                gt -= 1;
+                // The element swapped in may belong to the left part
+                observer.on_event(array, SortEvent::Compare(k, low))?;
+                if array[k] < p {
+                    array.swap(k, lt);
+                    observer.on_event(array, SortEvent::Swap(k, lt))?;
+                    lt += 1;
+                }
+            }
+        }
+        k += 1;
+    }
+    // Place the pivots between the three parts
+    lt -= 1;
+    gt += 1;
+    array.swap(low, lt);
+    observer.on_event(array, SortEvent::Swap(low, lt))?;
+    array.swap(high, gt);
+    observer.on_event(array, SortEvent::Swap(high, gt))?;
+    ControlFlow::Continue((lt, gt))
+}
+
+// Returns the index of the median of the elements at a, b and c
+fn median_of_three(
+    array: &[u32],
+    a: usize,
+    b: usize,
+    c: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    observer.on_event(array, SortEvent::Compare(a, b))?;
+    let (small, large) = if array[a] <= array[b] { (a, b) } else { (b, a) };
+    observer.on_event(array, SortEvent::Compare(large, c))?;
+    if array[c] >= array[large] {
+        return ControlFlow::Continue(large);
+    }
+    observer.on_event(array, SortEvent::Compare(small, c))?;
+    if array[c] <= array[small] {
+        return ControlFlow::Continue(small);
+    }
+    ControlFlow::Continue(c)
+}
+
+// A Quick Sort with a configured partition scheme and pivot strategy
+struct QuickSort {
+    scheme: PartitionScheme,
+    strategy: PivotStrategy,
+    rng: StdRng,
+}
+
+impl QuickSort {
+    // Returns the index of the pivot of array[low..=high] (the elements are not moved)
+    fn choose_pivot(
+        &mut self,
+        array: &[u32],
+        low: usize,
+        high: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<(), usize> {
+        let middle = low + (high - low) / 2;
+        match self.strategy {
+            PivotStrategy::First => ControlFlow::Continue(low),
+            PivotStrategy::Last => ControlFlow::Continue(high),
+            PivotStrategy::Middle => ControlFlow::Continue(middle),
+            PivotStrategy::Random => ControlFlow::Continue(self.rng.random_range(low..=high)),
+            PivotStrategy::MedianOfThree => median_of_three(array, low, middle, high, observer),
+            PivotStrategy::Ninther if high - low < 8 => {
+                median_of_three(array, low, middle, high, observer)
+            }
+            PivotStrategy::Ninther => {
+                let step = (high - low) / 8;
+                let first = median_of_three(array, low, low + step, low + 2 * step, observer)?;
+                let second =
+                    median_of_three(array, middle - step, middle, middle + step, observer)?;
+                let third = median_of_three(array, high - 2 * step, high - step, high, observer)?;
+                median_of_three(array, first, second, third, observer)
+            }
         }
-        quick_sort_recursive(
-            array,
-            pi + 1,
-            high,
-            window,
-            buffer,
-            sink,
-            use_sound,
-            bar_width,
-            num_bars,
-        );
     }
-    // After a sub-array is sorted, mark its elements as sorted (green)
-    // This is a simplification for visualization; in true Quick Sort, elements are not 'sorted' until the very end.
-    // However, for visual feedback, we can mark elements as sorted when their partition is complete.
-    if low <= high && high < array.len() {
-        // Ensure indices are valid
-        draw_bars(array, buffer, None, None, Some(low), bar_width); // Mark from low onwards as sorted
-        window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
+
+    // Moves the chosen pivot to the index the partition scheme expects it at
+    fn move_pivot(
+        array: &mut [u32],
+        from: usize,
+        to: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        if from != to {
+            array.swap(from, to);
+            observer.on_event(array, SortEvent::Swap(from, to))?;
+        }
+        ControlFlow::Continue(())
     }
+
+    // Sorts array[low..high] (high exclusive)
+    fn sort_range(
+        &mut self,
+        array: &mut [u32],
+        low: usize,
+        high: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        if high - low < 2 {
+            if high > low {
+                // A partition with a single element is already in place
+                observer.on_event(array, SortEvent::MarkSorted(low))?;
+            }
+            return ControlFlow::Continue(());
+        }
+        let last = high - 1;
+        match self.scheme {
+            PartitionScheme::Lomuto | PartitionScheme::Hoare => {
+                let pivot = self.choose_pivot(array, low, last, observer)?;
+                let pi = if self.scheme == PartitionScheme::Lomuto {
+                    Self::move_pivot(array, pivot, last, observer)?;
+                    partition(array, low, last, observer)?
+                } else {
+                    Self::move_pivot(array, pivot, low, observer)?;
+                    hoare_partition(array, low, last, observer)?
+                };
+                // The pivot is now in its final position
+                observer.on_event(array, SortEvent::MarkSorted(pi))?;
+                self.sort_range(array, low, pi, observer)?;
+                self.sort_range(array, pi + 1, high, observer)
+            }
+            PartitionScheme::ThreeWay => {
+                let pivot = self.choose_pivot(array, low, last, observer)?;
+                Self::move_pivot(array, pivot, low, observer)?;
+                let (lt, gt) = three_way_partition(array, low, last, observer)?;
+                // Every element equal to the pivot is in its final position
+                for i in lt..=gt {
+                    observer.on_event(array, SortEvent::MarkSorted(i))?;
+                }
+                self.sort_range(array, low, lt, observer)?;
+                self.sort_range(array, gt + 1, high, observer)
+            }
+            PartitionScheme::DualPivot => {
+                // One pivot is chosen from each half of the range
+                let middle = low + (last - low) / 2;
+                let first = self.choose_pivot(array, low, middle, observer)?;
+                let second = self.choose_pivot(array, middle + 1, last, observer)?;
+                Self::move_pivot(array, first, low, observer)?;
+                Self::move_pivot(array, second, last, observer)?;
+                let (lt, gt) = dual_pivot_partition(array, low, last, observer)?;
+                // Both pivots are now in their final positions
+                observer.on_event(array, SortEvent::MarkSorted(lt))?;
+                observer.on_event(array, SortEvent::MarkSorted(gt))?;
+                self.sort_range(array, low, lt, observer)?;
+                self.sort_range(array, lt + 1, gt, observer)?;
+                self.sort_range(array, gt + 1, high, observer)
+            }
+        }
+    }
+}
+
+// Implements Quick Sort with the given partition scheme and pivot strategy,
+// reporting every step to the observer
+// array: The array of numbers to be sorted (mutable)
+// seed: Seeds the RNG of random pivots
+// observer: Receives the pivots, comparisons, swaps and sorted positions as events
+pub fn quick_sort_with(
+    array: &mut [u32],
+    scheme: PartitionScheme,
+    strategy: PivotStrategy,
+    seed: u64,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let mut quick_sort = QuickSort {
+        scheme,
+        strategy,
+        rng: StdRng::seed_from_u64(seed),
+    };
+    quick_sort.sort_range(array, 0, array.len(), observer)?;
+    // Every element is in its final position
+    observer.on_event(array, SortEvent::Done)
 }
 
-// Wrapper for Quick Sort visualization
+// Wrapper for Quick Sort, reporting every step to the observer
+// Lomuto partitioning with the last element as the pivot
 pub fn quick_sort_visualized(
     array: &mut [u32],
-    window: &mut Window,
-    buffer: &mut [u32],
-    sink: &Sink,
-    use_sound: bool,
-    bar_width: usize,
-    num_bars: usize,
-) {
-    let n = array.len();
-    if n == 0 {
-        return;
-    } // Handle empty array
-    quick_sort_recursive(
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    // The last element is not a random pivot, any seed will do
+    quick_sort_with(
         array,
+        PartitionScheme::Lomuto,
+        PivotStrategy::Last,
         0,
-        n - 1,
-        window,
-        buffer,
-        sink,
-        use_sound,
-        bar_width,
-        num_bars,
-    );
-    // Final draw to ensure all bars are green
-    draw_bars(array, buffer, None, None, Some(0), bar_width);
-    window.update_with_buffer(buffer, WIDTH, HEIGHT).unwrap();
+        observer,
+    )
 }
@@ -0,0 +1,133 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Ids of the auxiliary buffers reported by the radix sorts
+pub const COUNTS: usize = 0; // Number of elements per digit value
+pub const BUCKETS: usize = 1; // The elements distributed by their digit, before being copied back
+
+// The digit of value at the place value exp (a power of base)
+fn digit(value: u32, exp: u64, base: u32) -> usize {
+    (value as u64 / exp % base as u64) as usize
+}
+
+// Distributes array[low..high] into the buckets by the digit at place value exp and copies them back
+// Returns the start of every bucket (relative to low) followed by high - low
+fn distribute(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    exp: u64,
+    base: u32,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), Vec<usize>> {
+    // Count the elements per digit
+    let mut counts = vec![0; base as usize];
+    observer.on_event(array, SortEvent::AuxBuffer(COUNTS, base as usize))?;
+    for i in low..high {
+        observer.on_event(array, SortEvent::Read(i))?;
+        let d = digit(array[i], exp, base);
+        counts[d] += 1;
+        observer.on_event(array, SortEvent::AuxWrite(COUNTS, d, counts[d] as u32))?;
+    }
+
+    // The prefix sums of the counts are where every bucket starts
+    let mut starts = Vec::with_capacity(counts.len() + 1);
+    let mut total = 0;
+    for count in &counts {
+        starts.push(total);
+        total += count;
+    }
+    starts.push(total);
+
+    // Move every element into its bucket, keeping the order of equal digits (stable)
+    let mut buckets = vec![0; high - low];
+    let mut next = starts.clone();
+    for i in low..high {
+        observer.on_event(array, SortEvent::Read(i))?;
+        let d = digit(array[i], exp, base);
+        buckets[next[d]] = array[i];
+        observer.on_event(array, SortEvent::AuxWrite(BUCKETS, low + next[d], array[i]))?;
+        next[d] += 1;
+    }
+
+    // Copy the buckets back into the array
+    for (offset, &value) in buckets.iter().enumerate() {
+        array[low + offset] = value;
+        observer.on_event(array, SortEvent::Write(low + offset, value))?;
+    }
+    ControlFlow::Continue(starts)
+}
+
+// Implements LSD (least significant digit first) Radix Sort, reporting every step to the observer
+// Every pass distributes the elements by one digit, starting with the lowest one
+// array: The array of numbers to be sorted (mutable)
+// base: The number of digit values (e.g. 10 for decimal digits)
+// observer: Receives the reads, counts, buckets and writes as events
+pub fn radix_lsd_sort_visualized(
+    array: &mut [u32],
+    base: u32,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    let max = array.iter().copied().max().unwrap_or(0) as u64;
+    observer.on_event(array, SortEvent::AuxBuffer(BUCKETS, n))?;
+    let mut exp = 1;
+    while max / exp > 0 {
+        distribute(array, 0, n, exp, base, observer)?;
+        exp *= base as u64;
+    }
+    observer.on_event(array, SortEvent::Done)
+}
+
+// Recursive function for MSD Radix Sort: sorts array[low..high] by the digits from exp down
+fn radix_msd_recursive(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    exp: u64,
+    base: u32,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    if high - low <= 1 || exp == 0 {
+        // A single element, or elements equal in every digit, are in their final position
+        for i in low..high {
+            observer.on_event(array, SortEvent::MarkSorted(i))?;
+        }
+        return ControlFlow::Continue(());
+    }
+    let starts = distribute(array, low, high, exp, base, observer)?;
+    // Sort every bucket by the remaining digits
+    for bucket in starts.windows(2) {
+        radix_msd_recursive(
+            array,
+            low + bucket[0],
+            low + bucket[1],
+            exp / base as u64,
+            base,
+            observer,
+        )?;
+    }
+    ControlFlow::Continue(())
+}
+
+// Implements MSD (most significant digit first) Radix Sort, reporting every step to the observer
+// The elements are distributed by their highest digit, then every bucket is sorted on its own
+// array: The array of numbers to be sorted (mutable)
+// base: The number of digit values (e.g. 10 for decimal digits)
+// observer: Receives the reads, counts, buckets, writes and sorted positions as events
+pub fn radix_msd_sort_visualized(
+    array: &mut [u32],
+    base: u32,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    let max = array.iter().copied().max().unwrap_or(0) as u64;
+    // Place value of the highest digit
+    let mut exp = 1;
+    while max / exp >= base as u64 {
+        exp *= base as u64;
+    }
+    observer.on_event(array, SortEvent::AuxBuffer(BUCKETS, n))?;
+    radix_msd_recursive(array, 0, n, exp, base, observer)?;
+    observer.on_event(array, SortEvent::Done)
+}
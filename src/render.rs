@@ -0,0 +1,184 @@
+use crate::draw_bars;
+use crate::events::{SortEvent, SortObserver};
+use crate::history::HistoryState;
+use crate::{HEIGHT, WIDTH};
+
+// Turns the event stream of a sorting algorithm into frames of bars.
+// Keeps track of which elements are already in their final position so every
+// frame can be drawn from the current array and the visual state alone.
+pub struct BarRenderer {
+    pub buffer: Vec<u32>, // The pixel buffer holding the last drawn frame
+    sorted: Vec<bool>,    // Elements that reached their final position
+    pivots: Vec<usize>,   // Pivots of the current partition (yellow)
+    highlights: (Option<usize>, Option<usize>), // Bars touched by the last event (red)
+    bar_width: usize,
+    bars_height: usize, // Height of the area the bars stand in, the rest is left to the view
+    view: Option<Box<dyn SortView>>, // Algorithm specific drawing over the bars
+    show_view: bool,
+}
+
+impl BarRenderer {
+    // num_bars: The number of elements in the array being sorted
+    // bar_width: The calculated width of each bar
+    pub fn new(num_bars: usize, bar_width: usize) -> Self {
+        BarRenderer {
+            buffer: vec![0; WIDTH * HEIGHT],
+            sorted: vec![false; num_bars],
+            pivots: Vec::new(),
+            highlights: (None, None),
+            bar_width,
+            bars_height: HEIGHT,
+            view: None,
+            show_view: true,
+        }
+    }
+
+    // Draws the view of the algorithm (e.g. its auxiliary arrays) into every frame
+    // The bars make room for the panels of the view at the bottom of the frame
+    pub fn set_view(&mut self, view: Box<dyn SortView>) {
+        self.bars_height = HEIGHT - view.panel_height().min(HEIGHT);
+        self.view = Some(view);
+    }
+
+    // Shows or hides the view in the next frames
+    pub fn toggle_view(&mut self) {
+        self.show_view = !self.show_view;
+    }
+
+    // Draws the array without any highlighted bars
+    pub fn draw(&mut self, array: &[u32]) {
+        self.highlights = (None, None);
+        self.redraw(array);
+    }
+
+    // Updates the visual state from an event and redraws the buffer if needed
+    // Returns true when the event produced a new frame
+    pub fn apply(&mut self, array: &[u32], event: SortEvent) -> bool {
+        if !self.update(array, event) {
+            return false;
+        }
+        self.redraw(array);
+        true
+    }
+
+    // Updates the visual state from an event without drawing it
+    // Returns true when the event calls for a new frame
+    pub fn update(&mut self, array: &[u32], event: SortEvent) -> bool {
+        if let Some(view) = &mut self.view {
+            // Views only follow the events, they never abort the sort
+            let _ = view.on_event(array, event);
+        }
+        apply_pivots(&mut self.pivots, event);
+        match event {
+            SortEvent::MarkSorted(i) => {
+                if let Some(sorted) = self.sorted.get_mut(i) {
+                    *sorted = true;
+                }
+            }
+            // Draw the final sorted state (all green)
+            SortEvent::Done => self.sorted = vec![true; array.len()],
+            _ => {}
+        }
+        if !draws_frame(event) {
+            return false;
+        }
+        self.highlights = highlights(event);
+        true
+    }
+
+    // Draws a state rebuilt from a History, leaving the live visual state untouched
+    // The view follows the live sort, so it is left out
+    pub fn draw_state(&mut self, state: &HistoryState) {
+        let (highlight_idx1, highlight_idx2) = highlights(state.event);
+        draw_bars(
+            &state.array,
+            &mut self.buffer,
+            highlight_idx1,
+            highlight_idx2,
+            &state.sorted,
+            &state.pivots,
+            self.bar_width,
+            self.bars_height,
+        );
+    }
+
+    // Draws the array with the current visual state into the buffer
+    pub fn redraw(&mut self, array: &[u32]) {
+        draw_bars(
+            array,
+            &mut self.buffer,
+            self.highlights.0,
+            self.highlights.1,
+            &self.sorted,
+            &self.pivots,
+            self.bar_width,
+            self.bars_height,
+        );
+        if let Some(view) = self.view.as_ref().filter(|_| self.show_view) {
+            view.draw(&mut self.buffer);
+        }
+    }
+}
+
+// The bars highlighted (red) in the frame of an event
+fn highlights(event: SortEvent) -> (Option<usize>, Option<usize>) {
+    match event {
+        // Compared or swapped elements are both highlighted in red
+        SortEvent::Compare(a, b) | SortEvent::Swap(a, b) => (Some(a), Some(b)),
+        // A written or read element is highlighted on its own
+        SortEvent::Write(i, _) | SortEvent::Read(i) => (Some(i), None),
+        // The other side of a comparison with a buffer is drawn by the view of the buffer
+        SortEvent::AuxCompare(.., index) => (Some(index), None),
+        _ => (None, None),
+    }
+}
+
+// Updates the pivots of the current partition from an event
+// A pivot is followed through swaps until it is marked sorted in its final position
+pub fn apply_pivots(pivots: &mut Vec<usize>, event: SortEvent) {
+    match event {
+        SortEvent::Pivot(i) if !pivots.contains(&i) => pivots.push(i),
+        SortEvent::Swap(a, b) => {
+            for pivot in pivots.iter_mut() {
+                if *pivot == a {
+                    *pivot = b;
+                } else if *pivot == b {
+                    *pivot = a;
+                }
+            }
+        }
+        SortEvent::MarkSorted(i) => pivots.retain(|&pivot| pivot != i),
+        SortEvent::Done => pivots.clear(),
+        _ => {}
+    }
+}
+
+// Whether the renderer draws a new frame for the event
+// Events that only change the visual state show up in the next frame
+pub fn draws_frame(event: SortEvent) -> bool {
+    !matches!(
+        event,
+        SortEvent::MarkSorted(_)
+            | SortEvent::Pivot(_)
+            | SortEvent::AuxBuffer(..)
+            | SortEvent::Gap(_)
+            | SortEvent::Run(..)
+            | SortEvent::Phase(_)
+            | SortEvent::Buffer(..)
+            | SortEvent::Layer(_)
+            | SortEvent::Flip(_)
+            | SortEvent::Tree(..)
+    )
+}
+
+// Algorithm specific drawing on top of the bars (e.g. the tree of a heap)
+// A view follows the event stream like any other observer and keeps what it needs to draw.
+pub trait SortView: SortObserver {
+    fn draw(&self, buffer: &mut [u32]);
+
+    // Height of the area at the bottom of the window the view draws its panels in
+    // The bars are drawn above it
+    fn panel_height(&self) -> usize {
+        0
+    }
+}
@@ -0,0 +1,180 @@
+use crate::block::block_size;
+use crate::draw::fill_rect;
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, GLYPH_HEIGHT};
+use crate::render::SortView;
+use crate::tim::min_run_length;
+use crate::{HEIGHT, WIDTH};
+use std::ops::ControlFlow;
+
+// Height of the panel below the bars
+const PANEL_HEIGHT: usize = 100;
+const PANEL_MARGIN: usize = 16; // Kept free at the bottom for the timeline
+const LABEL_SCALE: usize = 2;
+const LABEL_HEIGHT: usize = (GLYPH_HEIGHT + 3) * LABEL_SCALE;
+const STRIP_HEIGHT: usize = 20;
+
+const UNSORTED_COLOR: u32 = 0x0000FF; // The color draw_bars uses for unsorted bars
+const RUN_COLORS: [u32; 2] = [0x0000FF, 0xA000FF]; // Neighbouring runs on the stack
+const NEWEST_RUN_COLOR: u32 = 0x00A0FF; // The run reported last (detected, extended or merged)
+const BUFFER_COLOR: u32 = 0xFF8000; // The internal buffer (e.g. the keys of Block Sort)
+const SEPARATOR_COLOR: u32 = 0x404040;
+const LABEL_COLOR: u32 = 0xFFFFFF;
+const PHASE_COLOR: u32 = 0xFFFF00;
+
+// Shows the runs of a run based merge sort (e.g. TimSort): the bars are colored by the run
+// they belong to, and a panel beneath them lists the run stack and the current phase
+// An internal buffer (e.g. of Block Sort) is drawn in its own color
+pub struct RunView {
+    bar_width: usize,
+    header: String,            // Parameters of the algorithm, in front of the runs
+    runs_label: &'static str,  // What the runs are called in the panel
+    buffer: (usize, usize),    // Start and length of the internal buffer
+    runs: Vec<(usize, usize)>, // Start and length of the known runs, by start
+    newest: Option<(usize, usize)>, // The run reported last
+    phase: Option<&'static str>,
+}
+
+impl RunView {
+    // num_bars: The number of elements being sorted
+    pub fn new(num_bars: usize) -> Self {
+        RunView {
+            bar_width: WIDTH / num_bars.max(1),
+            header: format!("Minrun: {}", min_run_length(num_bars)),
+            runs_label: "Run stack",
+            buffer: (0, 0),
+            runs: Vec::new(),
+            newest: None,
+            phase: None,
+        }
+    }
+
+    // The view of Block Sort, which merges its runs bottom-up and keeps its keys at the front
+    pub fn blocks(num_bars: usize) -> Self {
+        RunView {
+            header: format!("Block size: {}", block_size(num_bars)),
+            runs_label: "Runs",
+            ..RunView::new(num_bars)
+        }
+    }
+
+    // Start and length of the internal buffer
+    pub fn buffer(&self) -> (usize, usize) {
+        self.buffer
+    }
+
+    // The runs on the stack, from the bottom (the start of the array) to the top
+    pub fn runs(&self) -> &[(usize, usize)] {
+        &self.runs
+    }
+
+    // Color of the bars of a run
+    fn run_color(&self, index: usize) -> u32 {
+        if self.newest == Some(self.runs[index]) {
+            NEWEST_RUN_COLOR
+        } else {
+            RUN_COLORS[index % RUN_COLORS.len()]
+        }
+    }
+
+    // The run an element belongs to, if any
+    fn run_of(&self, i: usize) -> Option<usize> {
+        self.runs
+            .iter()
+            .position(|&(start, len)| (start..start + len).contains(&i))
+    }
+}
+
+impl SortObserver for RunView {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        match event {
+            SortEvent::Run(start, len) => {
+                // The new run replaces the ones it overlaps (e.g. the two runs it was merged from)
+                self.runs.retain(|&(other, other_len)| {
+                    other + other_len <= start || start + len <= other
+                });
+                let index = self.runs.partition_point(|&(other, _)| other < start);
+                self.runs.insert(index, (start, len));
+                self.newest = Some((start, len));
+            }
+            SortEvent::Phase(name) => self.phase = Some(name),
+            SortEvent::Buffer(start, len) => self.buffer = (start, len),
+            SortEvent::Done => {
+                self.buffer = (0, 0);
+                self.runs.clear();
+                self.newest = None;
+                self.phase = None;
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for RunView {
+    fn draw(&self, buffer: &mut [u32]) {
+        let top = HEIGHT - PANEL_HEIGHT;
+        // Recolor the unsorted bars by run, leaving the highlighted ones as they are
+        let (buffer_start, buffer_len) = self.buffer;
+        for row in buffer[..top * WIDTH].chunks_exact_mut(WIDTH) {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if *pixel == UNSORTED_COLOR {
+                    let i = x / self.bar_width;
+                    if (buffer_start..buffer_start + buffer_len).contains(&i) {
+                        *pixel = BUFFER_COLOR;
+                    } else if let Some(run) = self.run_of(i) {
+                        *pixel = self.run_color(run);
+                    }
+                }
+            }
+        }
+
+        // The run stack as a strip of segments under the bars
+        let strip_y = top + PANEL_MARGIN / 2;
+        fill_rect(buffer, 0, top, WIDTH, 2, SEPARATOR_COLOR);
+        if buffer_len > 0 {
+            fill_rect(
+                buffer,
+                buffer_start * self.bar_width,
+                strip_y,
+                (buffer_len * self.bar_width).saturating_sub(2),
+                STRIP_HEIGHT,
+                BUFFER_COLOR,
+            );
+        }
+        for (index, &(start, len)) in self.runs.iter().enumerate() {
+            fill_rect(
+                buffer,
+                start * self.bar_width,
+                strip_y,
+                (len * self.bar_width).saturating_sub(2),
+                STRIP_HEIGHT,
+                self.run_color(index),
+            );
+        }
+
+        let lengths: Vec<String> = self.runs.iter().map(|(_, len)| len.to_string()).collect();
+        let mut label = self.header.clone();
+        if buffer_len > 0 {
+            label += &format!("   Buffer: {}", buffer_len);
+        }
+        label += &format!("   {}: {}", self.runs_label, lengths.join(" "));
+        let label_y = strip_y + STRIP_HEIGHT + PANEL_MARGIN / 2;
+        draw_text(
+            buffer,
+            PANEL_MARGIN,
+            label_y,
+            &label,
+            LABEL_SCALE,
+            LABEL_COLOR,
+        );
+        if let Some(phase) = self.phase {
+            let y = label_y + LABEL_HEIGHT;
+            draw_text(buffer, PANEL_MARGIN, y, phase, LABEL_SCALE, PHASE_COLOR);
+        }
+    }
+
+    fn panel_height(&self) -> usize {
+        PANEL_HEIGHT
+    }
+}
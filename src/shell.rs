@@ -0,0 +1,110 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// The gap sequences Shell Sort can use, each giving a different set of passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapSequence {
+    Shell,     // n/2, n/4, ..., 1 (Shell, 1959)
+    Knuth,     // 1, 4, 13, 40, ... = (3^k - 1) / 2, up to n/3 (Knuth, 1973)
+    Sedgewick, // 1, 8, 23, 77, 281, ... = 4^k + 3 * 2^(k-1) + 1 (Sedgewick, 1986)
+    Ciura,     // 1, 4, 10, 23, 57, 132, 301, 701, 1750, then * 2.25 (Ciura, 2001)
+    Tokuda,    // 1, 4, 9, 20, 46, 103, ... = ceil((9 * (9/4)^k - 4) / 5) (Tokuda, 1992)
+}
+
+impl GapSequence {
+    // The gaps used to sort n elements, largest first and always ending with 1
+    pub fn gaps(self, n: usize) -> Vec<usize> {
+        if n < 2 {
+            return Vec::new();
+        }
+        // The gaps are generated from 1 upwards
+        let mut gaps = vec![1];
+        match self {
+            GapSequence::Shell => {
+                let mut gap = n / 2;
+                while gap > 1 {
+                    gaps.insert(1, gap);
+                    gap /= 2;
+                }
+            }
+            GapSequence::Knuth => {
+                let mut gap = 4;
+                while gap <= n / 3 {
+                    gaps.push(gap);
+                    gap = 3 * gap + 1;
+                }
+            }
+            GapSequence::Sedgewick => {
+                let mut k = 1;
+                let mut gap = 8;
+                while gap < n {
+                    gaps.push(gap);
+                    k += 1;
+                    gap = (1 << (2 * k)) + 3 * (1 << (k - 1)) + 1;
+                }
+            }
+            GapSequence::Ciura => {
+                const CIURA: [usize; 8] = [4, 10, 23, 57, 132, 301, 701, 1750];
+                gaps.extend(CIURA.into_iter().take_while(|&gap| gap < n));
+                // Beyond the experimentally found gaps, each gap is 2.25 times the last
+                if gaps.len() > CIURA.len() {
+                    let mut gap = 1750 * 9 / 4;
+                    while gap < n {
+                        gaps.push(gap);
+                        gap = gap * 9 / 4;
+                    }
+                }
+            }
+            GapSequence::Tokuda => {
+                let mut power = 9.0 / 4.0_f64;
+                loop {
+                    let gap = ((9.0 * power - 4.0) / 5.0).ceil() as usize;
+                    if gap >= n {
+                        break;
+                    }
+                    gaps.push(gap);
+                    power *= 9.0 / 4.0;
+                }
+            }
+        }
+        gaps.reverse();
+        gaps
+    }
+}
+
+// Implements Shell Sort: insertion sorts of the subsequences array[k], array[k + gap], ...
+// for every gap of the sequence, so elements travel far in the early passes
+// array: The array of numbers to be sorted (mutable)
+// sequence: The gaps to use for the passes
+// observer: Receives the gaps, comparisons and shifts as events
+pub fn shell_sort_visualized(
+    array: &mut [u32],
+    sequence: GapSequence,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    for gap in sequence.gaps(n) {
+        // Report the gap of the pass, after it the array is gap-sorted
+        observer.on_event(array, SortEvent::Gap(gap))?;
+        for i in gap..n {
+            let key = array[i]; // The element to be inserted into its subsequence
+            let mut j = i;
+            // Shift the greater elements of the subsequence one gap ahead
+            while j >= gap {
+                observer.on_event(array, SortEvent::Compare(j - gap, j))?;
+                if array[j - gap] <= key {
+                    break;
+                }
+                array[j] = array[j - gap];
+                observer.on_event(array, SortEvent::Write(j, array[j]))?;
+                j -= gap;
+            }
+            // Place the key at its position in the subsequence (only needed if a shift occurred)
+            if j != i {
+                array[j] = key;
+                observer.on_event(array, SortEvent::Write(j, key))?;
+            }
+        }
+    }
+    observer.on_event(array, SortEvent::Done)
+}
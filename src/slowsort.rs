@@ -0,0 +1,38 @@
+use crate::budget::{Budget, OPERATION_BUDGET};
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Recursive function for Slowsort, sorting array[low..=high]
+fn slowsort_recursive(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    if low >= high {
+        return ControlFlow::Continue(());
+    }
+    // Find the maximum by sorting both halves, move it to the end, then sort the rest
+    let mid = low + (high - low) / 2;
+    slowsort_recursive(array, low, mid, observer)?;
+    slowsort_recursive(array, mid + 1, high, observer)?;
+    observer.on_event(array, SortEvent::Compare(mid, high))?;
+    if array[mid] > array[high] {
+        array.swap(mid, high);
+        observer.on_event(array, SortEvent::Swap(mid, high))?;
+    }
+    slowsort_recursive(array, low, high - 1, observer)
+}
+
+// Implements Slowsort, reporting every step to the observer
+// A parody of divide and conquer ("multiply and surrender"): it sorts both halves only to find
+// the maximum, then sorts everything but the maximum again. Takes more than polynomial time.
+// Gives up when it runs out of its operation budget.
+pub fn slowsort_visualized(array: &mut [u32], observer: &mut dyn SortObserver) -> ControlFlow<()> {
+    let mut budget = Budget::new(observer, OPERATION_BUDGET);
+    let result = match array.len() {
+        0 => ControlFlow::Continue(()),
+        n => slowsort_recursive(array, 0, n - 1, &mut budget),
+    };
+    budget.finish(array, result)
+}
@@ -0,0 +1,135 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// The Leonardo number of the order: L(0) = L(1) = 1, L(k) = L(k - 1) + L(k - 2) + 1
+pub fn leonardo(order: usize) -> usize {
+    let (mut a, mut b) = (1, 1);
+    for _ in 0..order {
+        (a, b) = (b, a + b + 1);
+    }
+    a
+}
+
+// Index of the root of a Leonardo heap, its last element
+fn root(tree: (usize, usize)) -> usize {
+    tree.0 + leonardo(tree.1) - 1
+}
+
+// Moves the root of the Leonardo heap of the order down until it is not less than its children
+// The children of the root are the heaps of order - 1 (left) and order - 2 (right) before it
+fn sift_down(
+    array: &mut [u32],
+    mut root: usize,
+    mut order: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    while order >= 2 {
+        let right = root - 1;
+        let left = right - leonardo(order - 2);
+        observer.on_event(array, SortEvent::Compare(left, right))?;
+        let (child, child_order) = if array[left] > array[right] {
+            (left, order - 1)
+        } else {
+            (right, order - 2)
+        };
+        observer.on_event(array, SortEvent::Compare(root, child))?;
+        if array[root] >= array[child] {
+            break;
+        }
+        array.swap(root, child);
+        observer.on_event(array, SortEvent::Swap(root, child))?;
+        root = child;
+        order = child_order;
+    }
+    ControlFlow::Continue(())
+}
+
+// Restores the forest after its tree at the index got a new root: the roots ascend from left
+// to right and every tree is a max-heap. The new root is swapped to the left along the roots
+// while the root before it is greater than the root and its children, then sifted down there.
+fn rectify(
+    array: &mut [u32],
+    trees: &[(usize, usize)],
+    mut index: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    while index > 0 {
+        let (current, previous) = (root(trees[index]), root(trees[index - 1]));
+        let order = trees[index].1;
+        let mut candidates = vec![current];
+        if order >= 2 {
+            candidates.push(current - 1 - leonardo(order - 2)); // Left child
+            candidates.push(current - 1); // Right child
+        }
+        let mut greater = true;
+        for candidate in candidates {
+            observer.on_event(array, SortEvent::Compare(previous, candidate))?;
+            if array[previous] <= array[candidate] {
+                greater = false;
+                break;
+            }
+        }
+        if !greater {
+            break;
+        }
+        array.swap(previous, current);
+        observer.on_event(array, SortEvent::Swap(previous, current))?;
+        index -= 1;
+    }
+    sift_down(array, root(trees[index]), trees[index].1, observer)
+}
+
+// Adds a tree to the forest and reports it to the observer
+fn push_tree(
+    array: &[u32],
+    trees: &mut Vec<(usize, usize)>,
+    tree: (usize, usize),
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    trees.push(tree);
+    observer.on_event(array, SortEvent::Tree(tree.0, leonardo(tree.1)))
+}
+
+// Implements Smoothsort, reporting every step to the observer
+// A Heap Sort on a forest of Leonardo heaps (heaps with a Leonardo number of elements) built
+// from left to right. On presorted input the new roots hardly move, so it approaches O(n)
+// instead of the O(n log n) of Heap Sort, which is why it is called adaptive.
+pub fn smooth_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let n = array.len();
+    // The forest, from left to right: start and order of every heap
+    let mut trees: Vec<(usize, usize)> = Vec::new();
+
+    // Construction: every element becomes the root of a new tree
+    for i in 0..n {
+        let len = trees.len();
+        if len >= 2 && trees[len - 2].1 == trees[len - 1].1 + 1 {
+            // The two last trees become the children of the new root
+            let tree = (trees[len - 2].0, trees[len - 2].1 + 1);
+            trees.truncate(len - 2);
+            push_tree(array, &mut trees, tree, observer)?;
+        } else if len >= 1 && trees[len - 1].1 == 1 {
+            push_tree(array, &mut trees, (i, 0), observer)?;
+        } else {
+            push_tree(array, &mut trees, (i, 1), observer)?;
+        }
+        rectify(array, &trees, trees.len() - 1, observer)?;
+    }
+
+    // Teardown: the root of the last tree is the maximum, removing it leaves its children
+    for i in (0..n).rev() {
+        if let Some((start, order)) = trees.pop() {
+            if order >= 2 {
+                push_tree(array, &mut trees, (start, order - 1), observer)?;
+                rectify(array, &trees, trees.len() - 1, observer)?;
+                let right = (start + leonardo(order - 1), order - 2);
+                push_tree(array, &mut trees, right, observer)?;
+                rectify(array, &trees, trees.len() - 1, observer)?;
+            }
+        }
+        observer.on_event(array, SortEvent::MarkSorted(i))?;
+    }
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,40 @@
+use crate::budget::BUDGET_EXHAUSTED;
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Counts the operations performed by a sorting algorithm
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SortStats {
+    pub comparisons: u64,
+    pub swaps: u64,
+    pub reads: u64, // Elements read from the array (two per comparison and per swap, one per read)
+    pub writes: u64, // Elements written into the array (two per swap)
+    pub aux_writes: u64, // Elements written into auxiliary buffers
+    pub gave_up: bool, // The algorithm ran out of its operation budget and left the array unsorted
+}
+
+impl SortObserver for SortStats {
+    fn on_event(&mut self, _array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        match event {
+            SortEvent::Compare(..) => {
+                self.comparisons += 1;
+                self.reads += 2;
+            }
+            SortEvent::Swap(..) => {
+                self.swaps += 1;
+                self.reads += 2;
+                self.writes += 2;
+            }
+            SortEvent::AuxCompare(..) => {
+                self.comparisons += 1;
+                self.reads += 1;
+            }
+            SortEvent::Read(..) => self.reads += 1,
+            SortEvent::Write(..) => self.writes += 1,
+            SortEvent::AuxWrite(..) => self.aux_writes += 1,
+            SortEvent::Phase(BUDGET_EXHAUSTED) => self.gave_up = true,
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
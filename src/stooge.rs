@@ -0,0 +1,41 @@
+use crate::budget::{Budget, OPERATION_BUDGET};
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Recursive function for Stooge Sort, sorting array[low..=high]
+fn stooge_sort_recursive(
+    array: &mut [u32],
+    low: usize,
+    high: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    observer.on_event(array, SortEvent::Compare(low, high))?;
+    if array[low] > array[high] {
+        array.swap(low, high);
+        observer.on_event(array, SortEvent::Swap(low, high))?;
+    }
+    let len = high - low + 1;
+    if len > 2 {
+        // Sort the first two thirds, the last two thirds, then the first two thirds again
+        let third = len / 3;
+        stooge_sort_recursive(array, low, high - third, observer)?;
+        stooge_sort_recursive(array, low + third, high, observer)?;
+        stooge_sort_recursive(array, low, high - third, observer)?;
+    }
+    ControlFlow::Continue(())
+}
+
+// Implements Stooge Sort, reporting every step to the observer
+// Sorts overlapping two thirds of the array three times over: O(n^2.71) comparisons,
+// worse than Bubble Sort. Gives up when it runs out of its operation budget.
+pub fn stooge_sort_visualized(
+    array: &mut [u32],
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    let mut budget = Budget::new(observer, OPERATION_BUDGET);
+    let result = match array.len() {
+        0 => ControlFlow::Continue(()),
+        n => stooge_sort_recursive(array, 0, n - 1, &mut budget),
+    };
+    budget.finish(array, result)
+}
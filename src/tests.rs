@@ -0,0 +1,918 @@
+use crate::audio::{ToneMixer, ToneOutput};
+use crate::auxiliary::AuxView;
+use crate::budget::{BUDGET_EXHAUSTED, OPERATION_BUDGET};
+use crate::cost::CostView;
+use crate::diagram::NetworkView;
+use crate::display::{Pacer, MAX_OPS_PER_SECOND};
+use crate::events::{NullObserver, SortEvent, SortObserver};
+use crate::export::{GifOptions, GifRecorder};
+use crate::headless::{HeadlessRecorder, TraceWriter};
+use crate::history::History;
+use crate::leonardo::LeonardoView;
+use crate::overlay::StatsOverlay;
+use crate::phase::PhaseView;
+use crate::quick::{PartitionScheme, PivotStrategy};
+use crate::render::SortView;
+use crate::render::{apply_pivots, draws_frame};
+use crate::runs::RunView;
+use crate::shell::GapSequence;
+use crate::stats::SortStats;
+use crate::tree::HeapTreeView;
+use crate::video::{VideoOptions, VideoRecorder, VideoTimeline, Y4mWriter};
+use crate::wav::{WavRenderer, WAV_SAMPLE_RATE};
+use crate::{ALGORITHMS, HEIGHT, WIDTH};
+use rand::Rng;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+// Values from 1 to len, like the arrays generated by the visualizer
+fn random_array(len: usize) -> Vec<u32> {
+    let mut rng = rand::rng();
+    (0..len)
+        .map(|_| rng.random_range(1..=(len as u32)))
+        .collect()
+}
+
+fn sorted_copy(array: &[u32]) -> Vec<u32> {
+    let mut sorted = array.to_vec();
+    sorted.sort_unstable();
+    sorted
+}
+
+#[test]
+fn every_algorithm_sorts() {
+    for algorithm in ALGORITHMS {
+        for len in [0, 1, 2, 3, 10, 100] {
+            // Slow algorithms are only run with as many bars as they can sort
+            let len = algorithm.max_bars.map_or(len, |max_bars| len.min(max_bars));
+            let mut array = random_array(len);
+            let expected = sorted_copy(&array);
+            assert!((algorithm.sort)(&mut array, 0, &mut NullObserver).is_continue());
+            assert_eq!(array, expected, "{} with {} bars", algorithm.name, len);
+        }
+    }
+}
+
+#[test]
+fn every_algorithm_sorts_special_inputs() {
+    let inputs: [Vec<u32>; 3] = [
+        (1..=64).collect(),       // Already sorted
+        (1..=64).rev().collect(), // Reversed
+        vec![7; 64],              // All equal
+    ];
+    for algorithm in ALGORITHMS {
+        for input in &inputs {
+            let len = algorithm
+                .max_bars
+                .map_or(input.len(), |max_bars| max_bars.min(64));
+            let input = &input[..len];
+            let mut array = input.to_vec();
+            assert!((algorithm.sort)(&mut array, 0, &mut NullObserver).is_continue());
+            assert_eq!(array, sorted_copy(input), "{}", algorithm.name);
+        }
+    }
+}
+
+// Observer that aborts the sort after a number of events
+struct AbortAfter(usize);
+
+impl SortObserver for AbortAfter {
+    fn on_event(&mut self, _array: &[u32], _event: SortEvent) -> ControlFlow<()> {
+        if self.0 == 0 {
+            return ControlFlow::Break(());
+        }
+        self.0 -= 1;
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn every_algorithm_stops_when_aborted() {
+    for algorithm in ALGORITHMS {
+        let mut array = random_array(100);
+        let mut events: Vec<SortEvent> = Vec::new();
+        let result = (algorithm.sort)(&mut array, 0, &mut (AbortAfter(50), &mut events));
+        assert!(result.is_break(), "{}", algorithm.name);
+        // The aborting observer comes first, so the recorder never sees the aborted event
+        assert_eq!(events.len(), 50, "{}", algorithm.name);
+        assert!(!events.contains(&SortEvent::Done), "{}", algorithm.name);
+    }
+}
+
+#[test]
+fn replaying_events_reproduces_the_sort() {
+    for algorithm in ALGORITHMS {
+        let initial = random_array(algorithm.max_bars.map_or(50, |max_bars| max_bars.min(50)));
+        let mut array = initial.clone();
+        let mut events: Vec<SortEvent> = Vec::new();
+        assert!((algorithm.sort)(&mut array, 0, &mut events).is_continue());
+
+        assert_eq!(events.last(), Some(&SortEvent::Done), "{}", algorithm.name);
+
+        // Swaps and writes are the only events that change the array
+        let mut replay = initial;
+        for event in events {
+            match event {
+                SortEvent::Swap(a, b) => replay.swap(a, b),
+                SortEvent::Write(i, value) => replay[i] = value,
+                _ => {}
+            }
+        }
+        assert_eq!(replay, array, "{}", algorithm.name);
+    }
+}
+
+#[test]
+fn trace_lists_the_initial_array_and_every_event() {
+    let mut array = vec![3, 1, 2];
+    let mut trace = TraceWriter::new(Vec::new(), &array);
+    assert!(crate::bubble::bubble_sort_visualized(&mut array, &mut trace).is_continue());
+    let output = String::from_utf8(trace.finish().unwrap()).unwrap();
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.first(), Some(&"array 3 1 2"));
+    assert_eq!(lines[1], "compare 0 1");
+    assert_eq!(lines[2], "swap 0 1");
+    assert_eq!(lines.last(), Some(&"done"));
+}
+
+#[test]
+fn gif_export_writes_a_complete_animation() {
+    let mut array = random_array(20);
+    let options = GifOptions {
+        frame_step: 3,
+        scale: 16,
+        delay_cs: 1,
+    };
+    let mut gif = GifRecorder::new(Vec::new(), 20, WIDTH / 20, options).unwrap();
+    assert!(crate::quick::quick_sort_visualized(&mut array, &mut gif).is_continue());
+    let bytes = gif.finish().unwrap();
+
+    assert!(bytes.starts_with(b"GIF89a"));
+    assert_eq!(bytes.last(), Some(&0x3B)); // GIF trailer
+}
+
+#[test]
+fn y4m_export_writes_one_frame_per_kept_step() {
+    let mut array = random_array(10);
+    let options = VideoOptions {
+        fps: 24,
+        frame_step: 2,
+        hold_final_frames: 3,
+    };
+    let y4m = Y4mWriter::new(Vec::new(), options.fps).unwrap();
+    let mut video = VideoRecorder::new(y4m, 10, WIDTH / 10, options);
+    let mut frames = HeadlessRecorder::new(10, WIDTH / 10);
+    let mut observer = (&mut video, &mut frames);
+    assert!(crate::insertion::insertion_sort_visualized(&mut array, &mut observer).is_continue());
+
+    // The final frame is always written and held, the others are decimated
+    let written = video.frames_written();
+    assert_eq!(written as u64, (frames.frames - 1).div_ceil(2) + 3);
+
+    let bytes = video.finish().unwrap().into_inner().unwrap();
+    let header = b"YUV4MPEG2 W1920 H1080 F24:1 Ip A1:1 C444\n";
+    assert!(bytes.starts_with(header));
+    let frame_size = b"FRAME\n".len() + WIDTH * HEIGHT * 3;
+    assert_eq!(bytes.len(), header.len() + written * frame_size);
+}
+
+#[test]
+fn wav_audio_lasts_as_long_as_the_video() {
+    let mut array = random_array(16);
+    let options = VideoOptions {
+        fps: 30,
+        frame_step: 3,
+        hold_final_frames: 30,
+    };
+    let mut events: Vec<SortEvent> = Vec::new();
+    let mut wav = WavRenderer::new(options, 15);
+    let mut observer = (&mut events, &mut wav);
+    assert!(crate::bubble::bubble_sort_visualized(&mut array, &mut observer).is_continue());
+
+    // Count the frames a video recorded with the same options would have
+    let mut timeline = VideoTimeline::new(options);
+    for event in events.into_iter().filter(|&event| draws_frame(event)) {
+        timeline.next_frame(event == SortEvent::Done);
+    }
+    let expected = timeline.frames_written() * WAV_SAMPLE_RATE as usize / 30;
+    assert_eq!(wav.samples().len(), expected);
+    assert!(wav.samples().iter().any(|&sample| sample != 0.0));
+
+    let mut file = std::io::Cursor::new(Vec::new());
+    wav.write(&mut file).unwrap();
+    let bytes = file.into_inner();
+    assert!(bytes.starts_with(b"RIFF"));
+    assert_eq!(bytes.len(), 44 + expected * 2); // 16-bit mono samples after the header
+}
+
+#[test]
+fn tone_mixer_never_blocks_and_mixes_the_tones() {
+    let (sink, mut output) = rodio::Sink::new_idle();
+    let mut mixer = ToneMixer::new(&sink);
+
+    // Far more tones than the queue holds, none of them may block
+    for value in 1..=1000 {
+        mixer.play(value, 15, 1000);
+    }
+    assert!(mixer.dropped > 0);
+
+    let samples: Vec<f32> = output.by_ref().take(4410).collect();
+    assert!(samples.iter().any(|&sample| sample != 0.0));
+    assert!(samples.iter().all(|&sample| (-1.0..=1.0).contains(&sample)));
+}
+
+#[test]
+fn pacer_batches_fast_operations_into_frames() {
+    let start = Instant::now();
+
+    // Slow speeds show every operation and wait for the next one
+    let mut slow = Pacer::new(10.0);
+    let pace = slow.step(start);
+    assert!(pace.present);
+    assert!(pace.wait > Duration::from_millis(90));
+
+    // Fast speeds only present one frame per frame interval
+    let mut fast = Pacer::new(100_000.0);
+    let now = start + Duration::from_millis(1);
+    let presented = (0..1000).filter(|_| fast.step(now).present).count();
+    assert_eq!(presented, 1);
+    assert!(fast.step(now + Duration::from_millis(20)).present);
+
+    // The speed stays within its limits
+    fast.set_ops_per_second(1e12);
+    assert_eq!(fast.ops_per_second(), MAX_OPS_PER_SECOND);
+}
+
+// Observer that keeps a copy of the array after every operation that draws a frame
+struct ArrayCapture(Vec<Vec<u32>>);
+
+impl SortObserver for ArrayCapture {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        if draws_frame(event) {
+            self.0.push(array.to_vec());
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn history_rebuilds_every_recorded_state() {
+    // Enough operations to need several snapshots
+    let initial = random_array(80);
+    let mut array = initial.clone();
+    let mut history = History::new(&initial);
+    let mut capture = ArrayCapture(Vec::new());
+    let mut observer = (&mut history, &mut capture);
+    assert!(crate::bubble::bubble_sort_visualized(&mut array, &mut observer).is_continue());
+
+    assert_eq!(history.len(), capture.0.len());
+    assert!(history.len() > 3 * 1024);
+    for (operation, expected) in capture.0.iter().enumerate() {
+        assert_eq!(&history.state_at(operation).array, expected);
+    }
+    let last = history.state_at(history.len() - 1);
+    assert_eq!(last.event, SortEvent::Done);
+    assert!(last.sorted.iter().all(|&sorted| sorted));
+}
+
+#[test]
+fn overlay_counts_the_operations_and_draws_them() {
+    let mut array = vec![4, 3, 2, 1];
+    let mut overlay = StatsOverlay::new("Merge Sort", 4);
+    assert!(crate::merge::merge_sort_visualized(&mut array, &mut overlay).is_continue());
+
+    // Every merge copies both halves into the auxiliary buffers and writes them back
+    assert_eq!(overlay.stats.aux_writes, 8);
+    assert_eq!(overlay.stats.writes, 8);
+    // Every comparison reads the right half from the array, the left half from its buffer
+    assert_eq!(overlay.stats.reads, overlay.stats.comparisons);
+    assert_eq!(overlay.lines()[0], "Merge Sort");
+    assert_eq!(overlay.lines()[1], "Bars: 4");
+
+    let mut buffer = vec![0x0000FF; WIDTH * HEIGHT];
+    overlay.draw(&mut buffer);
+    assert!(buffer.contains(&0xFFFFFF)); // The text
+    assert!(buffer.contains(&0x00003F)); // The darkened bars behind it
+    assert_eq!(buffer[(HEIGHT - 1) * WIDTH], 0x0000FF);
+}
+
+#[test]
+fn overlay_time_stands_still_while_paused() {
+    let mut overlay = StatsOverlay::new("Bubble Sort", 4);
+    assert!(overlay
+        .on_event(&[2, 1], SortEvent::Compare(0, 1))
+        .is_continue());
+    overlay.pause();
+    let paused = overlay.elapsed();
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(overlay.elapsed(), paused);
+
+    // Only the time after resuming counts, also once the sort is done
+    overlay.resume();
+    assert!(overlay.on_event(&[1, 2], SortEvent::Done).is_continue());
+    assert!(overlay.elapsed() < paused + Duration::from_millis(50));
+}
+
+#[test]
+fn heap_tree_follows_the_sift_down_path() {
+    // A max-heap except for the root, which has to sink to the bottom
+    let mut array = vec![1, 7, 6, 5, 4, 3, 2];
+    let mut view = HeapTreeView::new(array.len());
+    assert!(crate::heap::sift_down(&mut array, 0, 0, 7, &mut view).is_continue());
+    assert_eq!(array, vec![7, 5, 6, 1, 4, 3, 2]);
+    assert_eq!(view.path(), &[0, 1, 3]);
+
+    let mut buffer = vec![0; WIDTH * HEIGHT];
+    view.draw(&mut buffer);
+    assert!(buffer.contains(&0xFFFF00)); // The highlighted path
+
+    assert!(crate::heap::heap_sort_visualized(&mut array, &mut view).is_continue());
+    assert_eq!(view.heap_size(), 0);
+
+    // Swapping the root with itself is not a move down the tree
+    assert!(view.on_event(&array, SortEvent::Swap(0, 0)).is_continue());
+    assert!(view.path().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "shrink factor greater than 1")]
+fn comb_sort_rejects_a_shrink_factor_that_never_ends() {
+    let _ = crate::comb::comb_sort_visualized(&mut random_array(10), 1.0, &mut NullObserver);
+}
+
+#[test]
+fn gap_sequences_start_large_and_end_with_one() {
+    assert_eq!(GapSequence::Shell.gaps(100), vec![50, 25, 12, 6, 3, 1]);
+    assert_eq!(GapSequence::Knuth.gaps(100), vec![13, 4, 1]);
+    assert_eq!(GapSequence::Sedgewick.gaps(100), vec![77, 23, 8, 1]);
+    assert_eq!(GapSequence::Ciura.gaps(100), vec![57, 23, 10, 4, 1]);
+    assert_eq!(GapSequence::Tokuda.gaps(100), vec![46, 20, 9, 4, 1]);
+    assert_eq!(GapSequence::Ciura.gaps(5000)[0], 3937); // 1750 * 2.25
+    assert!(GapSequence::Knuth.gaps(1).is_empty());
+
+    // Every pass reports its gap before it starts
+    let mut array = random_array(100);
+    let mut events: Vec<SortEvent> = Vec::new();
+    let sort = crate::shell::shell_sort_visualized(&mut array, GapSequence::Ciura, &mut events);
+    assert!(sort.is_continue());
+    let gaps: Vec<usize> = events
+        .iter()
+        .filter_map(|event| match event {
+            SortEvent::Gap(gap) => Some(*gap),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(gaps, GapSequence::Ciura.gaps(100));
+}
+
+#[test]
+fn radix_sort_fills_the_count_and_bucket_buffers() {
+    let mut array = vec![25, 3, 15, 5];
+    let mut view = AuxView::new(&["Counts", "Buckets"]);
+    let mut observer = (AbortAfter(22), &mut view);
+    // Stop right after the first pass (counts, buckets and copying back)
+    let sort = crate::radix::radix_lsd_sort_visualized(&mut array, 10, &mut observer);
+    assert!(sort.is_break());
+    assert_eq!(view.buffer(crate::radix::COUNTS)[3], 1);
+    assert_eq!(view.buffer(crate::radix::COUNTS)[5], 3);
+    // Stable: the elements ending with 5 keep their order
+    assert_eq!(view.buffer(crate::radix::BUCKETS), &[3, 25, 15, 5]);
+    assert_eq!(array, vec![3, 25, 15, 5]);
+}
+
+#[test]
+fn counting_and_bucket_sort_drain_their_buffers() {
+    let mut array = vec![3, 1, 3, 2, 3];
+    let mut counts = AuxView::new(&["Counts"]);
+    assert!(crate::counting::counting_sort_visualized(&mut array, &mut counts).is_continue());
+    assert_eq!(array, vec![1, 2, 3, 3, 3]);
+    assert_eq!(counts.buffer(crate::counting::COUNTS), &[0, 0, 0, 0]);
+
+    let mut array = random_array(50);
+    let count = crate::bucket::bucket_count(50);
+    let mut buckets = AuxView::columns("Buckets", count);
+    assert!(crate::bucket::bucket_sort_visualized(&mut array, &mut buckets).is_continue());
+    // Every element went through a bucket and was taken out again
+    let lengths: usize = (0..count).map(|id| buckets.buffer(id).len()).sum();
+    assert_eq!(lengths, 50);
+    assert!((0..count).all(|id| buckets.buffer(id).iter().all(|&value| value == 0)));
+}
+
+#[test]
+fn merge_compares_the_left_half_from_its_buffer() {
+    let initial = random_array(64);
+    let mut events: Vec<SortEvent> = Vec::new();
+    assert!(crate::merge::merge_sort_visualized(&mut initial.clone(), &mut events).is_continue());
+
+    // Replay the buffers: every comparison must name the element still waiting in L[]
+    let mut array = initial;
+    let mut left: Vec<Option<u32>> = Vec::new();
+    let mut comparisons = 0;
+    for event in events {
+        match event {
+            SortEvent::AuxBuffer(0, len) => left = vec![None; len],
+            SortEvent::AuxWrite(0, i, value) => left[i] = Some(value),
+            SortEvent::AuxRead(0, i) => left[i] = None,
+            SortEvent::AuxCompare(id, i, value, index) => {
+                assert_eq!(id, 0);
+                assert_eq!(left[i], Some(value));
+                // The right half is compared where it still is in the array
+                assert!(index >= left.len());
+                comparisons += 1;
+            }
+            SortEvent::Compare(..) => panic!("merge compares against a stale slot"),
+            SortEvent::Write(i, value) => array[i] = value,
+            _ => {}
+        }
+    }
+    assert!(comparisons > 0);
+    assert_eq!(array, sorted_copy(&array));
+}
+
+#[test]
+fn merge_sort_drains_its_buffers_in_the_frames() {
+    let initial = random_array(16);
+    let mut events: Vec<SortEvent> = Vec::new();
+    assert!(crate::merge::merge_sort_visualized(&mut initial.clone(), &mut events).is_continue());
+    let last_merge = events
+        .iter()
+        .rposition(|&event| event == SortEvent::AuxBuffer(0, 8))
+        .unwrap();
+
+    let mut array = initial;
+    let mut view = AuxView::new(&["L", "R"]).with_array_scale(16);
+    let mut recorder = HeadlessRecorder::new(16, WIDTH / 16);
+    recorder.set_view(Box::new(AuxView::new(&["L", "R"]).with_array_scale(16)));
+    // Stop in the middle of the last merge, after copying both halves and merging a few elements
+    let mut observer = (
+        AbortAfter(last_merge + 2 + 16 + 10),
+        (&mut view, &mut recorder),
+    );
+    assert!(crate::merge::merge_sort_visualized(&mut array, &mut observer).is_break());
+
+    let remaining = |id| view.buffer(id).iter().filter(|&&value| value != 0).count();
+    assert_eq!(view.buffer(0).len(), 8);
+    assert!(remaining(0) + remaining(1) < 16);
+    assert!(remaining(0) + remaining(1) > 0);
+    // The panels are part of the recorded frames
+    assert!(recorder.buffer().contains(&0xC0C0C0));
+}
+
+#[test]
+fn exchange_sorts_follow_the_bubble_sort_conventions() {
+    // Cocktail Shaker Sort settles the largest element at the end, then the smallest at the start
+    let mut array = vec![3, 5, 1, 4, 2];
+    let mut events: Vec<SortEvent> = Vec::new();
+    assert!(
+        crate::cocktail::cocktail_shaker_sort_visualized(&mut array, &mut events).is_continue()
+    );
+    let sorted: Vec<SortEvent> = events
+        .iter()
+        .copied()
+        .filter(|event| matches!(event, SortEvent::MarkSorted(_)))
+        .take(2)
+        .collect();
+    assert_eq!(
+        sorted,
+        vec![SortEvent::MarkSorted(4), SortEvent::MarkSorted(0)]
+    );
+
+    // Comb Sort shrinks its gap by the factor and finishes with gap 1
+    let mut array = random_array(100);
+    let mut events: Vec<SortEvent> = Vec::new();
+    assert!(crate::comb::comb_sort_visualized(&mut array, 2.0, &mut events).is_continue());
+    let gaps: Vec<usize> = events
+        .iter()
+        .filter_map(|event| match event {
+            SortEvent::Gap(gap) => Some(*gap),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(gaps[..6], [50, 25, 12, 6, 3, 1]);
+    assert!(gaps[6..].iter().all(|&gap| gap == 1));
+    assert_eq!(array, sorted_copy(&array));
+}
+
+#[test]
+fn hybrid_sorts_report_their_phases() {
+    // Lomuto partitions of equal elements are as unbalanced as they get
+    let mut array = vec![7; 200];
+    let mut view = PhaseView::new();
+    assert!(crate::introsort::introsort_visualized(&mut array, &mut view).is_continue());
+    assert!(view.count("Heap Sort fallback") > 0);
+    assert_eq!(view.current(), None);
+
+    // A sorted array is finished by the partial insertion sort without partitioning it
+    let mut array: Vec<u32> = (1..=200).collect();
+    let mut view = PhaseView::new();
+    assert!(crate::pdq::pdqsort_visualized(&mut array, &mut view).is_continue());
+    assert_eq!(view.count("Partial insertion"), 1);
+    assert_eq!(view.count("Partition"), 0);
+
+    // A descending one is reversed first
+    let mut array: Vec<u32> = (1..=200).rev().collect();
+    let mut view = PhaseView::new();
+    assert!(crate::pdq::pdqsort_visualized(&mut array, &mut view).is_continue());
+    assert_eq!(view.count("Reversed"), 1);
+    assert_eq!(array, (1..=200).collect::<Vec<u32>>());
+
+    // Runs of equal elements are put in place by equal partitions
+    let mut array: Vec<u32> = (0..200).map(|i| i % 3).collect();
+    let mut view = PhaseView::new();
+    assert!(crate::pdq::pdqsort_visualized(&mut array, &mut view).is_continue());
+    assert!(view.count("Equal partition") > 0);
+    assert_eq!(array, sorted_copy(&array));
+}
+
+#[test]
+fn quick_sort_pivots_are_followed_until_they_are_sorted() {
+    let schemes = [
+        PartitionScheme::Lomuto,
+        PartitionScheme::Hoare,
+        PartitionScheme::ThreeWay,
+        PartitionScheme::DualPivot,
+    ];
+    let strategies = [
+        PivotStrategy::First,
+        PivotStrategy::Last,
+        PivotStrategy::Middle,
+        PivotStrategy::Random,
+        PivotStrategy::MedianOfThree,
+        PivotStrategy::Ninther,
+    ];
+    let initial = random_array(64);
+    for scheme in schemes {
+        for strategy in strategies {
+            let mut array = initial.clone();
+            let mut events: Vec<SortEvent> = Vec::new();
+            let sort = crate::quick::quick_sort_with(&mut array, scheme, strategy, 0, &mut events);
+            assert!(sort.is_continue());
+            assert_eq!(array, sorted_copy(&initial), "{:?} {:?}", scheme, strategy);
+            // Every pivot ends up marked sorted in its final position
+            let mut pivots = Vec::new();
+            for &event in &events[..events.len() - 1] {
+                apply_pivots(&mut pivots, event);
+            }
+            assert!(pivots.is_empty(), "{:?} {:?}", scheme, strategy);
+        }
+    }
+
+    // The pivot keeps its own color while it is compared
+    let mut array = random_array(16);
+    let mut recorder = HeadlessRecorder::new(16, WIDTH / 16);
+    let sort = crate::quick::quick_sort_visualized(&mut array, &mut (AbortAfter(2), &mut recorder));
+    assert!(sort.is_break());
+    assert!(recorder.buffer().contains(&0xFFFF00));
+}
+
+#[test]
+fn random_pivots_follow_the_seed() {
+    let initial = random_array(200);
+    let run = |seed| {
+        let mut events: Vec<SortEvent> = Vec::new();
+        let sort = crate::quick::quick_sort_with(
+            &mut initial.clone(),
+            PartitionScheme::Lomuto,
+            PivotStrategy::Random,
+            seed,
+            &mut events,
+        );
+        assert!(sort.is_continue());
+        events
+    };
+    // The same seed repeats the run event for event, another seed picks other pivots
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(43));
+}
+
+#[test]
+fn tim_sort_merges_runs_and_gallops() {
+    assert_eq!(crate::tim::min_run_length(31), 31);
+    assert_eq!(crate::tim::min_run_length(64), 16);
+    assert_eq!(crate::tim::min_run_length(400), 25);
+
+    // Two ascending runs whose blocks of 20 interleave, so every merge step has long winning streaks
+    let blocks = |parity: u32| (0..400).filter(move |value| value / 20 % 2 == parity);
+    let mut array: Vec<u32> = blocks(0).chain(blocks(1)).map(|value| value + 1).collect();
+    let mut phases = PhaseView::new();
+    let mut runs = RunView::new(400);
+    let sort = crate::tim::tim_sort_visualized(&mut array, &mut (&mut phases, &mut runs));
+    assert!(sort.is_continue());
+    assert_eq!(array, (1..=400).collect::<Vec<u32>>());
+    assert!(phases.count("Galloping") > 0);
+    assert_eq!(phases.count("Binary insertion"), 0);
+
+    // Random runs, ascending and descending, with many equal elements
+    let mut rng = rand::rng();
+    for _ in 0..200 {
+        let mut array = Vec::new();
+        let len = rng.random_range(0..300);
+        while array.len() < len {
+            let mut chunk: Vec<u32> = (0..rng.random_range(1..60))
+                .map(|_| rng.random_range(1..=20))
+                .collect();
+            chunk.sort_unstable();
+            if rng.random_bool(0.3) {
+                chunk.reverse();
+            }
+            array.extend(chunk);
+        }
+        let expected = sorted_copy(&array);
+        let mut runs = RunView::new(array.len());
+        assert!(crate::tim::tim_sort_visualized(&mut array, &mut runs).is_continue());
+        assert_eq!(array, expected);
+    }
+
+    // The runs are on the stack while the array is split up
+    let mut array = random_array(400);
+    let mut runs = RunView::new(400);
+    let sort = crate::tim::tim_sort_visualized(&mut array, &mut (AbortAfter(3000), &mut runs));
+    assert!(sort.is_break());
+    assert!(!runs.runs().is_empty());
+    // Only the run on top of the stack may still be extended to the minimum length
+    let (_, pushed) = runs.runs().split_last().unwrap();
+    assert!(pushed.iter().all(|&(_, len)| len >= 25));
+}
+
+#[test]
+fn merge_sort_variants_trade_memory_for_time() {
+    let initial = random_array(200);
+    let stats = |sort: fn(&mut [u32], &mut dyn SortObserver) -> ControlFlow<()>,
+                 initial: &[u32]| {
+        let mut array = initial.to_vec();
+        let mut stats = SortStats::default();
+        assert!(sort(&mut array, &mut stats).is_continue());
+        assert_eq!(array, sorted_copy(initial));
+        stats
+    };
+    let top_down = stats(crate::merge::merge_sort_visualized, &initial);
+    let bottom_up = stats(crate::merge::merge_sort_bottom_up_visualized, &initial);
+    let in_place = stats(crate::merge::merge_sort_in_place_visualized, &initial);
+    assert!(top_down.aux_writes > 0 && bottom_up.aux_writes > 0);
+    // Without buffers the elements are moved by many more swaps
+    assert_eq!(in_place.aux_writes, 0);
+    assert!(in_place.swaps > top_down.writes);
+
+    // A sorted array is a single natural run: one pass of comparisons, nothing merged
+    let sorted: Vec<u32> = (1..=200).collect();
+    let natural = stats(crate::merge::natural_merge_sort_visualized, &sorted);
+    assert_eq!(natural.comparisons, 199);
+    assert_eq!(natural.aux_writes, 0);
+}
+
+#[test]
+fn block_sort_merges_in_place_with_its_keys() {
+    // Distinct values: a full buffer of 2√n keys and block merges
+    let mut array = random_array(400);
+    let expected = sorted_copy(&array);
+    let mut phases = PhaseView::new();
+    let mut runs = RunView::blocks(400);
+    let mut stats = SortStats::default();
+    let sort = crate::block::block_sort_visualized(
+        &mut array,
+        &mut (&mut phases, &mut (&mut runs, &mut stats)),
+    );
+    assert!(sort.is_continue());
+    assert_eq!(array, expected);
+    assert_eq!(stats.aux_writes, 0);
+    for phase in [
+        "Collecting keys",
+        "Block selection",
+        "Block rotation",
+        "Local merge",
+    ] {
+        assert!(phases.count(phase) > 0, "{}", phase);
+    }
+    assert_eq!(phases.count("Rotation merge"), 0);
+    assert_eq!(runs.buffer(), (0, 0));
+
+    // The keys stay in front while the rest is merged
+    let mut array = (1..=400).rev().collect::<Vec<u32>>();
+    let mut runs = RunView::blocks(400);
+    let sort = crate::block::block_sort_visualized(&mut array, &mut (AbortAfter(5000), &mut runs));
+    assert!(sort.is_break());
+    assert_eq!(runs.buffer(), (0, 40));
+
+    // Few distinct values leave too few keys to tag the blocks
+    let mut rng = rand::rng();
+    for len in 0..300 {
+        let distinct = rng.random_range(1..=len.max(1) as u32);
+        let mut array: Vec<u32> = (0..len).map(|_| rng.random_range(1..=distinct)).collect();
+        let expected = sorted_copy(&array);
+        let sort = crate::block::block_sort_visualized(&mut array, &mut NullObserver);
+        assert!(sort.is_continue());
+        assert_eq!(array, expected);
+    }
+}
+
+#[test]
+fn sorting_networks_sort_every_size() {
+    for (name, network) in [
+        ("bitonic", crate::network::bitonic_network as fn(usize) -> _),
+        ("odd-even merge", crate::network::odd_even_merge_network),
+    ] {
+        for n in 0..=12 {
+            let layers = network(n);
+            // The comparators of a layer touch every element at most once
+            for layer in &layers {
+                let mut touched = vec![false; n];
+                for &(i, j) in layer {
+                    assert!(i < j && j < n, "{} {}", name, n);
+                    assert!(!touched[i] && !touched[j], "{} {}", name, n);
+                    touched[i] = true;
+                    touched[j] = true;
+                }
+            }
+            // 0-1 principle: a network sorting every sequence of zeros and ones sorts everything
+            for bits in 0..1u32 << n {
+                let mut array: Vec<u32> = (0..n).map(|i| bits >> i & 1).collect();
+                let expected = sorted_copy(&array);
+                let sort = crate::network::network_sort(&mut array, &layers, &mut NullObserver);
+                assert!(sort.is_continue());
+                assert_eq!(array, expected, "{} {}", name, n);
+            }
+        }
+    }
+    // Bitonic Sort has log2(n) * (log2(n) + 1) / 2 layers for powers of two
+    assert_eq!(crate::network::bitonic_network(16).len(), 10);
+    assert_eq!(crate::network::odd_even_merge_network(16).len(), 10);
+
+    // The view follows the layers and shows every comparator as executed in the end
+    let mut array = random_array(100);
+    let mut view = NetworkView::new(100, crate::network::bitonic_network(100));
+    let sort =
+        crate::network::bitonic_sort_visualized(&mut array, &mut (AbortAfter(1000), &mut view));
+    assert!(sort.is_break());
+    assert!(view.current_layer().is_some_and(|layer| layer > 0));
+    let mut view = NetworkView::new(100, crate::network::odd_even_merge_network(100));
+    assert!(crate::network::odd_even_merge_sort_visualized(&mut array, &mut view).is_continue());
+    assert_eq!(view.current_layer(), None);
+}
+
+#[test]
+fn cycle_and_pancake_sort_count_their_cost() {
+    // Cycle Sort writes exactly the elements that are out of place
+    for initial in [
+        random_array(200),
+        vec![3, 1, 2, 2, 1, 3, 3],
+        (1..=50).collect(),
+    ] {
+        let mut array = initial.clone();
+        let mut writes = CostView::writes();
+        let mut stats = SortStats::default();
+        let sort = crate::cycle::cycle_sort_visualized(&mut array, &mut (&mut writes, &mut stats));
+        assert!(sort.is_continue());
+        assert_eq!(array, sorted_copy(&initial));
+        assert_eq!(Some(writes.count()), writes.bound());
+        assert_eq!(stats.writes, writes.count() as u64);
+    }
+
+    // Pancake Sort only moves elements by flips, and needs at most 2n - 3 of them
+    for n in [0, 1, 2, 3, 200] {
+        let initial = random_array(n);
+        let mut array = initial.clone();
+        let mut flips = CostView::flips(n);
+        let mut events: Vec<SortEvent> = Vec::new();
+        let sort =
+            crate::pancake::pancake_sort_visualized(&mut array, &mut (&mut flips, &mut events));
+        assert!(sort.is_continue());
+        assert_eq!(array, sorted_copy(&initial));
+        assert!(flips.count() <= (2 * n).saturating_sub(3));
+        let mut flip_len = 0;
+        for event in events {
+            match event {
+                SortEvent::Flip(len) => flip_len = len,
+                // Every swap mirrors two elements of the flipped prefix
+                SortEvent::Swap(i, j) => assert_eq!(i + j, flip_len - 1),
+                SortEvent::Write(..) => panic!("Pancake Sort wrote an element"),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn smoothsort_adapts_to_presorted_input() {
+    assert_eq!(
+        (0..8).map(crate::smooth::leonardo).collect::<Vec<_>>(),
+        [1, 1, 3, 5, 9, 15, 25, 41]
+    );
+    for initial in [random_array(300), (1..=300).rev().collect(), vec![2; 50]] {
+        let mut array = initial.clone();
+        assert!(crate::smooth::smooth_sort_visualized(&mut array, &mut NullObserver).is_continue());
+        assert_eq!(array, sorted_copy(&initial));
+    }
+
+    // Sorted input needs no swaps and a linear number of comparisons, unlike Heap Sort
+    let sorted: Vec<u32> = (1..=1000).collect();
+    let stats = |sort: fn(&mut [u32], &mut dyn SortObserver) -> ControlFlow<()>| {
+        let mut array = sorted.clone();
+        let mut stats = SortStats::default();
+        assert!(sort(&mut array, &mut stats).is_continue());
+        assert_eq!(array, sorted);
+        stats
+    };
+    let smooth = stats(crate::smooth::smooth_sort_visualized);
+    let heap = stats(crate::heap::heap_sort_visualized);
+    assert_eq!(smooth.swaps, 0);
+    assert!(smooth.comparisons < 4 * 1000);
+    assert!(heap.comparisons > 2 * smooth.comparisons);
+
+    // The forest covers the unsorted part with trees of Leonardo sizes, largest first
+    let mut array = random_array(300);
+    let mut forest = LeonardoView::new(300);
+    let sort =
+        crate::smooth::smooth_sort_visualized(&mut array, &mut (AbortAfter(3000), &mut forest));
+    assert!(sort.is_break());
+    let trees = forest.trees();
+    assert!(!trees.is_empty() && trees[0].0 == 0);
+    assert!(trees
+        .windows(2)
+        .all(|pair| pair[0].0 + pair[0].1 == pair[1].0));
+    assert!(trees.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+}
+
+#[test]
+fn slow_sorts_stay_within_their_budget() {
+    for algorithm in ALGORITHMS
+        .iter()
+        .filter(|algorithm| algorithm.family == "Novelty Sorts")
+    {
+        let max_bars = algorithm.max_bars.expect(algorithm.name);
+        // Within the bar limit the sort finishes within the budget, even on distinct values
+        let mut array: Vec<u32> = (1..=max_bars as u32).rev().collect();
+        let mut stats = SortStats::default();
+        assert!((algorithm.sort)(&mut array, 0, &mut stats).is_continue());
+        assert!(!stats.gave_up, "{}", algorithm.name);
+        assert!(array.is_sorted(), "{}", algorithm.name);
+
+        // Far beyond it the budget stops the sort, which ends normally but unsorted
+        let initial: Vec<u32> = (1..=1000).rev().collect();
+        let mut array = initial.clone();
+        let mut phases = PhaseView::new();
+        let mut stats = SortStats::default();
+        let mut events: Vec<SortEvent> = Vec::new();
+        let mut observer = (&mut phases, (&mut stats, &mut events));
+        let sort = (algorithm.sort)(&mut array, 0, &mut observer);
+        assert!(sort.is_continue(), "{}", algorithm.name);
+        assert!(stats.gave_up, "{}", algorithm.name);
+        assert_eq!(
+            phases.current(),
+            Some(BUDGET_EXHAUSTED),
+            "{}",
+            algorithm.name
+        );
+        assert_eq!(
+            stats.comparisons + stats.swaps,
+            OPERATION_BUDGET,
+            "{}",
+            algorithm.name
+        );
+        assert!(!array.is_sorted(), "{}", algorithm.name);
+        // Every operation performed before giving up was reported, up to the last one
+        let mut replay = initial;
+        for event in events {
+            match event {
+                SortEvent::Swap(a, b) => replay.swap(a, b),
+                SortEvent::Write(i, value) => replay[i] = value,
+                _ => {}
+            }
+        }
+        assert_eq!(replay, array, "{}", algorithm.name);
+
+        // Aborting the sort still aborts it
+        let mut array = random_array(max_bars);
+        let sort = (algorithm.sort)(&mut array, 0, &mut AbortAfter(10));
+        assert!(sort.is_break(), "{}", algorithm.name);
+    }
+}
+
+#[test]
+fn budget_tells_its_own_abort_from_the_observers() {
+    // The observer aborts on the very operation that uses up the budget
+    let mut array: Vec<u32> = (1..=1000).rev().collect();
+    let mut stats = SortStats::default();
+    let mut observer = (AbortAfter(OPERATION_BUDGET as usize - 1), &mut stats);
+    let sort = crate::bogo::bogo_sort_visualized(&mut array, 0, &mut observer);
+    assert!(sort.is_break());
+    assert!(!stats.gave_up);
+    assert_eq!(stats.comparisons + stats.swaps, OPERATION_BUDGET - 1);
+}
+
+#[test]
+fn random_shuffles_follow_the_seed() {
+    let initial: Vec<u32> = (1..=6).rev().collect();
+    for sort in [
+        crate::bogo::bogo_sort_visualized,
+        crate::bogo::bozo_sort_visualized,
+    ] {
+        let run = |seed| {
+            let mut events: Vec<SortEvent> = Vec::new();
+            assert!(sort(&mut initial.clone(), seed, &mut events).is_continue());
+            events
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+}
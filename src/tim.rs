@@ -0,0 +1,608 @@
+use crate::events::{SortEvent, SortObserver};
+use std::ops::ControlFlow;
+
+// Arrays shorter than this are sorted with a single binary insertion sort
+// (the constants are the ones of Java's TimSort, whose smaller runs are easier to follow)
+const MIN_MERGE: usize = 32;
+// Number of elements in a row one run has to win in a merge before galloping starts
+const MIN_GALLOP: usize = 7;
+
+// The id of the temporary buffer holding the shorter run of a merge
+pub const TEMP: usize = 0;
+
+// Minimum length of the runs the array is split into (between MIN_MERGE / 2 and MIN_MERGE),
+// chosen so the number of runs is a power of two or slightly less, which keeps the merges balanced
+pub fn min_run_length(mut n: usize) -> usize {
+    let mut r = 0; // Becomes 1 if any bit shifted out is set
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+// A sorted run searched by a gallop, either in the array or in the temporary buffer
+struct Run<'a> {
+    values: &'a [u32],
+    shown_at: usize, // Index of the array the first element is highlighted at
+}
+
+// Finds where the key goes in the run: the number of elements that come before it
+// Probes at distances 1, 3, 7, 15, ... from the hint, then binary searches the last step
+// key: The value and the index it is highlighted at
+// before: Whether an element of the run comes before the key
+fn gallop(
+    array: &[u32],
+    key: (u32, usize),
+    run: Run,
+    hint: usize,
+    before: fn(u32, u32) -> bool,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    let (value, key_index) = key;
+    let probe = |i: usize, observer: &mut dyn SortObserver| {
+        observer.on_event(array, SortEvent::Compare(run.shown_at + i, key_index))?;
+        ControlFlow::Continue(before(run.values[i], value))
+    };
+    let len = run.values.len() as isize;
+    let hint = hint as isize;
+    let mut last_ofs: isize = 0;
+    let mut ofs: isize = 1;
+    if probe(hint as usize, observer)? {
+        // Gallop right until run[hint + last_ofs] comes before the key and run[hint + ofs] does not
+        let max_ofs = len - hint;
+        while ofs < max_ofs && probe((hint + ofs) as usize, observer)? {
+            last_ofs = ofs;
+            ofs = (ofs << 1) + 1;
+        }
+        ofs = ofs.min(max_ofs);
+        last_ofs += hint;
+        ofs += hint;
+    } else {
+        // Gallop left until run[hint - ofs] comes before the key and run[hint - last_ofs] does not
+        let max_ofs = hint + 1;
+        while ofs < max_ofs && !probe((hint - ofs) as usize, observer)? {
+            last_ofs = ofs;
+            ofs = (ofs << 1) + 1;
+        }
+        ofs = ofs.min(max_ofs);
+        (last_ofs, ofs) = (hint - ofs, hint - last_ofs);
+    }
+    // The key goes somewhere in run[last_ofs + 1..=ofs]
+    last_ofs += 1;
+    while last_ofs < ofs {
+        let m = last_ofs + (ofs - last_ofs) / 2;
+        if probe(m as usize, observer)? {
+            last_ofs = m + 1;
+        } else {
+            ofs = m;
+        }
+    }
+    ControlFlow::Continue(ofs as usize)
+}
+
+// Index of the first element of the run that is not less than the key
+fn gallop_left(
+    array: &[u32],
+    key: (u32, usize),
+    run: Run,
+    hint: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    gallop(
+        array,
+        key,
+        run,
+        hint,
+        |element, key| element < key,
+        observer,
+    )
+}
+
+// Index of the first element of the run that is greater than the key (equal elements stay first)
+fn gallop_right(
+    array: &[u32],
+    key: (u32, usize),
+    run: Run,
+    hint: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), usize> {
+    gallop(
+        array,
+        key,
+        run,
+        hint,
+        |element, key| element <= key,
+        observer,
+    )
+}
+
+// Writes a value to the array and reports it
+fn write(
+    array: &mut [u32],
+    i: usize,
+    value: u32,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    array[i] = value;
+    observer.on_event(array, SortEvent::Write(i, value))
+}
+
+// Moves an element of the temporary buffer back into the array
+fn write_from_temp(
+    array: &mut [u32],
+    i: usize,
+    temp: &[u32],
+    t: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<()> {
+    write(array, i, temp[t], observer)?;
+    observer.on_event(array, SortEvent::AuxRead(TEMP, t))
+}
+
+// Copies array[from..from + len] into the temporary buffer
+fn copy_to_temp(
+    array: &[u32],
+    from: usize,
+    len: usize,
+    observer: &mut dyn SortObserver,
+) -> ControlFlow<(), Vec<u32>> {
+    observer.on_event(array, SortEvent::AuxBuffer(TEMP, len))?;
+    let temp = array[from..from + len].to_vec();
+    for (t, &value) in temp.iter().enumerate() {
+        observer.on_event(array, SortEvent::AuxWrite(TEMP, t, value))?;
+    }
+    ControlFlow::Continue(temp)
+}
+
+// TimSort state kept between the runs
+struct TimSort {
+    runs: Vec<(usize, usize)>, // The run stack: start and length of every pending run
+    min_gallop: usize,         // Adapts to how well galloping paid off so far
+}
+
+impl TimSort {
+    // Finds the run starting at low and makes it ascending (strictly descending runs are reversed)
+    // Returns its length
+    fn count_run_and_make_ascending(
+        array: &mut [u32],
+        low: usize,
+        high: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<(), usize> {
+        observer.on_event(array, SortEvent::Phase("Run detection"))?;
+        let mut run_high = low + 1;
+        if run_high == high {
+            return ControlFlow::Continue(1);
+        }
+        observer.on_event(array, SortEvent::Compare(run_high, low))?;
+        let descending = array[run_high] < array[low];
+        run_high += 1;
+        while run_high < high {
+            observer.on_event(array, SortEvent::Compare(run_high, run_high - 1))?;
+            let in_order = if descending {
+                array[run_high] < array[run_high - 1]
+            } else {
+                array[run_high] >= array[run_high - 1]
+            };
+            if !in_order {
+                break;
+            }
+            run_high += 1;
+        }
+        if descending {
+            // Strictly descending, so reversing it keeps equal elements in order
+            let (mut i, mut j) = (low, run_high - 1);
+            while i < j {
+                array.swap(i, j);
+                observer.on_event(array, SortEvent::Swap(i, j))?;
+                i += 1;
+                j -= 1;
+            }
+        }
+        ControlFlow::Continue(run_high - low)
+    }
+
+    // Extends the sorted run array[low..start] to array[low..high] with binary insertion
+    fn binary_sort(
+        array: &mut [u32],
+        low: usize,
+        high: usize,
+        start: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        observer.on_event(array, SortEvent::Phase("Binary insertion"))?;
+        for i in start..high {
+            let pivot = array[i];
+            // Find the first element greater than the pivot (equal elements stay first)
+            let (mut left, mut right) = (low, i);
+            while left < right {
+                let mid = left + (right - left) / 2;
+                observer.on_event(array, SortEvent::Compare(i, mid))?;
+                if pivot < array[mid] {
+                    right = mid;
+                } else {
+                    left = mid + 1;
+                }
+            }
+            // Shift the greater elements one position right and put the pivot in the gap
+            for j in (left + 1..=i).rev() {
+                write(array, j, array[j - 1], observer)?;
+            }
+            if left != i {
+                write(array, left, pivot, observer)?;
+            }
+            observer.on_event(array, SortEvent::Run(low, i + 1 - low))?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // Merges runs until the lengths on the stack satisfy the TimSort invariants:
+    // every run is longer than the next one, and longer than the next two together
+    fn merge_collapse(
+        &mut self,
+        array: &mut [u32],
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        while self.runs.len() > 1 {
+            let runs = &self.runs;
+            let len = |i: usize| runs[i].1;
+            let mut n = runs.len() - 2;
+            if (n > 0 && len(n - 1) <= len(n) + len(n + 1))
+                || (n > 1 && len(n - 2) <= len(n) + len(n - 1))
+            {
+                // Merge the middle run with the shorter of its neighbours
+                if len(n - 1) < len(n + 1) {
+                    n -= 1;
+                }
+            } else if len(n) > len(n + 1) {
+                break;
+            }
+            self.merge_at(array, n, observer)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // Merges all runs left on the stack, once the whole array has been split into runs
+    fn merge_force_collapse(
+        &mut self,
+        array: &mut [u32],
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            if n > 0 && self.runs[n - 1].1 < self.runs[n + 1].1 {
+                n -= 1;
+            }
+            self.merge_at(array, n, observer)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // Merges the runs at i and i + 1 of the stack
+    fn merge_at(
+        &mut self,
+        array: &mut [u32],
+        i: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        observer.on_event(array, SortEvent::Phase("Merging"))?;
+        let (start, len1) = self.runs[i];
+        let (base2, len2) = self.runs[i + 1];
+        self.runs[i].1 = len1 + len2;
+        self.runs.remove(i + 1);
+
+        // Elements of the first run that are not greater than the start of the second one
+        // are already in place
+        let key = (array[base2], base2);
+        let run = Run {
+            values: &array[start..start + len1],
+            shown_at: start,
+        };
+        let k = gallop_right(array, key, run, 0, observer)?;
+        let (base1, len1) = (start + k, len1 - k);
+        if len1 > 0 {
+            // And so are the elements of the second run that are not less than the end of the first one
+            let key = (array[base1 + len1 - 1], base1 + len1 - 1);
+            let run = Run {
+                values: &array[base2..base2 + len2],
+                shown_at: base2,
+            };
+            let len2 = gallop_left(array, key, run, len2 - 1, observer)?;
+            if len2 > 0 {
+                // Copy the shorter run out of the way
+                if len1 <= len2 {
+                    self.merge_lo(array, base1, len1, base2, len2, observer)?;
+                } else {
+                    self.merge_hi(array, base1, len1, base2, len2, observer)?;
+                }
+            }
+        }
+        observer.on_event(array, SortEvent::Run(start, self.runs[i].1))
+    }
+
+    // Merges the adjacent runs array[base1..base1 + len1] and array[base2..base2 + len2]
+    // from the left, with the first (shorter) run in the temporary buffer
+    // The first element of the second run goes first and the last one of the first run goes last
+    fn merge_lo(
+        &mut self,
+        array: &mut [u32],
+        base1: usize,
+        mut len1: usize,
+        base2: usize,
+        mut len2: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        let temp = copy_to_temp(array, base1, len1, observer)?;
+        let mut cursor1 = 0; // Next element of the first run, in the temporary buffer
+        let mut cursor2 = base2; // Next element of the second run
+        let mut dest = base1; // Next slot to fill
+
+        write(array, dest, array[cursor2], observer)?;
+        dest += 1;
+        cursor2 += 1;
+        len2 -= 1;
+        let mut min_gallop = self.min_gallop;
+        if len2 > 0 && len1 > 1 {
+            'outer: loop {
+                // Elements in a row taken from the first and from the second run
+                let mut count1 = 0;
+                let mut count2 = 0;
+                // One element at a time until one run keeps winning
+                loop {
+                    // The element of the first run would be at dest
+                    observer.on_event(array, SortEvent::Compare(dest, cursor2))?;
+                    if array[cursor2] < temp[cursor1] {
+                        write(array, dest, array[cursor2], observer)?;
+                        dest += 1;
+                        cursor2 += 1;
+                        count2 += 1;
+                        count1 = 0;
+                        len2 -= 1;
+                        if len2 == 0 {
+                            break 'outer;
+                        }
+                    } else {
+                        write_from_temp(array, dest, &temp, cursor1, observer)?;
+                        dest += 1;
+                        cursor1 += 1;
+                        count1 += 1;
+                        count2 = 0;
+                        len1 -= 1;
+                        if len1 == 1 {
+                            break 'outer;
+                        }
+                    }
+                    if count1.max(count2) >= min_gallop {
+                        break;
+                    }
+                }
+
+                // Galloping: find where the next element of each run goes in the other one
+                // and move everything before it at once
+                observer.on_event(array, SortEvent::Phase("Galloping"))?;
+                loop {
+                    let run = Run {
+                        values: &temp[cursor1..cursor1 + len1],
+                        shown_at: dest,
+                    };
+                    count1 = gallop_right(array, (array[cursor2], cursor2), run, 0, observer)?;
+                    for _ in 0..count1 {
+                        write_from_temp(array, dest, &temp, cursor1, observer)?;
+                        dest += 1;
+                        cursor1 += 1;
+                    }
+                    len1 -= count1;
+                    if len1 <= 1 {
+                        break 'outer;
+                    }
+                    write(array, dest, array[cursor2], observer)?;
+                    dest += 1;
+                    cursor2 += 1;
+                    len2 -= 1;
+                    if len2 == 0 {
+                        break 'outer;
+                    }
+
+                    let run = Run {
+                        values: &array[cursor2..cursor2 + len2],
+                        shown_at: cursor2,
+                    };
+                    count2 = gallop_left(array, (temp[cursor1], dest), run, 0, observer)?;
+                    for _ in 0..count2 {
+                        write(array, dest, array[cursor2], observer)?;
+                        dest += 1;
+                        cursor2 += 1;
+                    }
+                    len2 -= count2;
+                    if len2 == 0 {
+                        break 'outer;
+                    }
+                    write_from_temp(array, dest, &temp, cursor1, observer)?;
+                    dest += 1;
+                    cursor1 += 1;
+                    len1 -= 1;
+                    if len1 == 1 {
+                        break 'outer;
+                    }
+                    // Galloping pays off, start it sooner next time
+                    min_gallop = min_gallop.saturating_sub(1);
+                    if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                        break;
+                    }
+                }
+                // Back to one element at a time, and start galloping later next time
+                min_gallop += 2;
+                observer.on_event(array, SortEvent::Phase("Merging"))?;
+            }
+        }
+        self.min_gallop = min_gallop.max(1);
+
+        if len1 == 1 {
+            // The rest of the second run goes before the last element of the first one
+            for _ in 0..len2 {
+                write(array, dest, array[cursor2], observer)?;
+                dest += 1;
+                cursor2 += 1;
+            }
+            write_from_temp(array, dest, &temp, cursor1, observer)
+        } else {
+            // The second run is used up, the rest of the first one goes last
+            for _ in 0..len1 {
+                write_from_temp(array, dest, &temp, cursor1, observer)?;
+                dest += 1;
+                cursor1 += 1;
+            }
+            ControlFlow::Continue(())
+        }
+    }
+
+    // Merges the adjacent runs array[base1..base1 + len1] and array[base2..base2 + len2]
+    // from the right, with the second (shorter) run in the temporary buffer
+    // The last element of the first run goes last and the first one of the second run goes first
+    fn merge_hi(
+        &mut self,
+        array: &mut [u32],
+        base1: usize,
+        mut len1: usize,
+        base2: usize,
+        mut len2: usize,
+        observer: &mut dyn SortObserver,
+    ) -> ControlFlow<()> {
+        let temp = copy_to_temp(array, base2, len2, observer)?;
+        // The elements left to merge fill array[base1..base1 + len1 + len2]:
+        // the next element of the first run is at base1 + len1 - 1, the next one of the second
+        // run is temp[len2 - 1] and the next slot to fill is the last one
+        let dest = |len1: usize, len2: usize| base1 + len1 + len2 - 1;
+
+        write(array, dest(len1, len2), array[base1 + len1 - 1], observer)?;
+        len1 -= 1;
+        let mut min_gallop = self.min_gallop;
+        if len1 > 0 && len2 > 1 {
+            'outer: loop {
+                let mut count1 = 0;
+                let mut count2 = 0;
+                loop {
+                    // The element of the second run would be at the slot being filled
+                    let cursor1 = base1 + len1 - 1;
+                    observer.on_event(array, SortEvent::Compare(cursor1, dest(len1, len2)))?;
+                    if temp[len2 - 1] < array[cursor1] {
+                        write(array, dest(len1, len2), array[cursor1], observer)?;
+                        count1 += 1;
+                        count2 = 0;
+                        len1 -= 1;
+                        if len1 == 0 {
+                            break 'outer;
+                        }
+                    } else {
+                        write_from_temp(array, dest(len1, len2), &temp, len2 - 1, observer)?;
+                        count2 += 1;
+                        count1 = 0;
+                        len2 -= 1;
+                        if len2 == 1 {
+                            break 'outer;
+                        }
+                    }
+                    if count1.max(count2) >= min_gallop {
+                        break;
+                    }
+                }
+
+                observer.on_event(array, SortEvent::Phase("Galloping"))?;
+                loop {
+                    let key = (temp[len2 - 1], dest(len1, len2));
+                    let run = Run {
+                        values: &array[base1..base1 + len1],
+                        shown_at: base1,
+                    };
+                    count1 = len1 - gallop_right(array, key, run, len1 - 1, observer)?;
+                    for _ in 0..count1 {
+                        write(array, dest(len1, len2), array[base1 + len1 - 1], observer)?;
+                        len1 -= 1;
+                    }
+                    if len1 == 0 {
+                        break 'outer;
+                    }
+                    write_from_temp(array, dest(len1, len2), &temp, len2 - 1, observer)?;
+                    len2 -= 1;
+                    if len2 == 1 {
+                        break 'outer;
+                    }
+
+                    // The remaining elements of the second run would end at the slot being filled
+                    let key = (array[base1 + len1 - 1], base1 + len1 - 1);
+                    let run = Run {
+                        values: &temp[..len2],
+                        shown_at: dest(len1, len2) + 1 - len2,
+                    };
+                    count2 = len2 - gallop_left(array, key, run, len2 - 1, observer)?;
+                    for _ in 0..count2 {
+                        write_from_temp(array, dest(len1, len2), &temp, len2 - 1, observer)?;
+                        len2 -= 1;
+                    }
+                    if len2 <= 1 {
+                        break 'outer;
+                    }
+                    write(array, dest(len1, len2), array[base1 + len1 - 1], observer)?;
+                    len1 -= 1;
+                    if len1 == 0 {
+                        break 'outer;
+                    }
+                    min_gallop = min_gallop.saturating_sub(1);
+                    if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                        break;
+                    }
+                }
+                min_gallop += 2;
+                observer.on_event(array, SortEvent::Phase("Merging"))?;
+            }
+        }
+        self.min_gallop = min_gallop.max(1);
+
+        if len2 == 1 {
+            // The rest of the first run goes after the first element of the second one
+            while len1 > 0 {
+                write(array, dest(len1, len2), array[base1 + len1 - 1], observer)?;
+                len1 -= 1;
+            }
+            write_from_temp(array, base1, &temp, 0, observer)
+        } else {
+            // The first run is used up, the rest of the second one goes first
+            for t in (0..len2).rev() {
+                write_from_temp(array, base1 + t, &temp, t, observer)?;
+            }
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+// Implements TimSort, reporting every step to the observer
+// Splits the array into natural runs (reversing descending ones), extends short runs to the
+// minimum run length with binary insertion, and merges the runs on a stack, galloping through
+// the parts of a merge where one run keeps winning
+// array: The array of numbers to be sorted (mutable)
+// observer: Receives the runs, phases, comparisons, writes and the temporary buffer as events
+pub fn tim_sort_visualized(array: &mut [u32], observer: &mut dyn SortObserver) -> ControlFlow<()> {
+    let n = array.len();
+    let min_run = if n < MIN_MERGE { n } else { min_run_length(n) };
+    let mut tim_sort = TimSort {
+        runs: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+    let mut low = 0;
+    while low < n {
+        let mut run_len = TimSort::count_run_and_make_ascending(array, low, n, observer)?;
+        observer.on_event(array, SortEvent::Run(low, run_len))?;
+        if run_len < min_run {
+            // Too short, extend it to the minimum run length (or the end of the array)
+            let force = min_run.min(n - low);
+            TimSort::binary_sort(array, low, low + force, low + run_len, observer)?;
+            run_len = force;
+        }
+        tim_sort.runs.push((low, run_len));
+        tim_sort.merge_collapse(array, observer)?;
+        low += run_len;
+    }
+    tim_sort.merge_force_collapse(array, observer)?;
+    observer.on_event(array, SortEvent::Done)
+}
@@ -0,0 +1,145 @@
+use crate::draw::{darken_rect, draw_line, draw_rect, fill_rect};
+use crate::events::{SortEvent, SortObserver};
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::render::SortView;
+use std::ops::ControlFlow;
+
+// Levels of the heap drawn by the tree overlay (the deeper nodes only appear as bars)
+const TREE_LEVELS: usize = 5;
+const TREE_NODES: usize = (1 << TREE_LEVELS) - 1;
+
+// Area of the window used by the tree, to the right of the statistics overlay
+const TREE_LEFT: usize = 560;
+const TREE_TOP: usize = 12;
+const TREE_WIDTH: usize = 1340;
+const TREE_LEVEL_HEIGHT: usize = 72;
+const NODE_WIDTH: usize = 60;
+const NODE_HEIGHT: usize = 30;
+const TEXT_SCALE: usize = 2;
+
+const NODE_BACKGROUND: u32 = 0x202020;
+const NODE_BORDER: u32 = 0x0000FF; // Same blue as the unsorted bars
+const EDGE_COLOR: u32 = 0x808080;
+const PATH_COLOR: u32 = 0xFFFF00; // Nodes and edges of the current sift-down path
+const HIGHLIGHT_COLOR: u32 = 0xFF0000; // Nodes touched by the last operation
+const TEXT_COLOR: u32 = 0xFFFFFF;
+
+// Draws the top levels of the binary heap kept in the unsorted part of the array
+// The nodes the current sift-down moved the root through are highlighted as a path.
+pub struct HeapTreeView {
+    values: Vec<u32>, // Values of the drawn nodes
+    heap_size: usize, // Elements before the first one marked sorted
+    path: Vec<usize>, // Nodes of the current sift-down, from the top
+    highlights: (Option<usize>, Option<usize>),
+}
+
+impl HeapTreeView {
+    // num_bars: The number of elements being sorted
+    pub fn new(num_bars: usize) -> Self {
+        HeapTreeView {
+            values: Vec::new(),
+            heap_size: num_bars,
+            path: Vec::new(),
+            highlights: (None, None),
+        }
+    }
+
+    // Number of elements currently in the heap
+    pub fn heap_size(&self) -> usize {
+        self.heap_size
+    }
+
+    // Nodes of the current sift-down path
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    // Center of the top edge of a node
+    fn node_position(node: usize) -> (usize, usize) {
+        let level = (node + 1).ilog2() as usize;
+        let first = (1 << level) - 1;
+        let slot = TREE_WIDTH >> level;
+        let x = TREE_LEFT + (node - first) * slot + slot / 2;
+        (x, TREE_TOP + 36 + level * TREE_LEVEL_HEIGHT)
+    }
+}
+
+impl SortObserver for HeapTreeView {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        let shown = array.len().min(TREE_NODES);
+        self.values.clear();
+        self.values.extend_from_slice(&array[..shown]);
+        match event {
+            SortEvent::Compare(a, b) => self.highlights = (Some(a), Some(b)),
+            SortEvent::Swap(a, b) => {
+                self.highlights = (Some(a), Some(b));
+                let (parent, child) = (a.min(b), a.max(b));
+                // Only swaps between a parent and its child move an element down the tree
+                if child > 0 && (child - 1) / 2 == parent {
+                    if self.path.last() != Some(&parent) {
+                        self.path = vec![parent];
+                    }
+                    self.path.push(child);
+                }
+            }
+            SortEvent::MarkSorted(i) => {
+                self.heap_size = self.heap_size.min(i);
+                self.path.clear();
+            }
+            SortEvent::Done => {
+                self.heap_size = 0;
+                self.path.clear();
+                self.highlights = (None, None);
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SortView for HeapTreeView {
+    fn draw(&self, buffer: &mut [u32]) {
+        let nodes = self.heap_size.min(self.values.len());
+        let levels = if nodes > 0 {
+            nodes.ilog2() as usize + 1
+        } else {
+            0
+        };
+        darken_rect(
+            buffer,
+            TREE_LEFT,
+            TREE_TOP - 6,
+            TREE_WIDTH,
+            42 + levels * TREE_LEVEL_HEIGHT,
+        );
+        let title = format!("Heap: {} nodes", self.heap_size);
+        draw_text(buffer, TREE_LEFT + 12, TREE_TOP + 6, &title, 2, TEXT_COLOR);
+
+        // Edges first, so the nodes are drawn over them
+        for node in 1..nodes {
+            let parent = (node - 1) / 2;
+            let on_path = self.path.contains(&node) && self.path.contains(&parent);
+            let (x0, y0) = Self::node_position(parent);
+            let (x1, y1) = Self::node_position(node);
+            let color = if on_path { PATH_COLOR } else { EDGE_COLOR };
+            draw_line(buffer, (x0, y0 + NODE_HEIGHT), (x1, y1), color);
+        }
+        for node in 0..nodes {
+            let (x, y) = Self::node_position(node);
+            let left = x - NODE_WIDTH / 2;
+            let border = if Some(node) == self.highlights.0 || Some(node) == self.highlights.1 {
+                HIGHLIGHT_COLOR
+            } else if self.path.contains(&node) {
+                PATH_COLOR
+            } else {
+                NODE_BORDER
+            };
+            fill_rect(buffer, left, y, NODE_WIDTH, NODE_HEIGHT, NODE_BACKGROUND);
+            draw_rect(buffer, left, y, NODE_WIDTH, NODE_HEIGHT, border);
+            let text = self.values[node].to_string();
+            let text_x = x - text_width(&text, TEXT_SCALE) / 2;
+            let text_y = y + (NODE_HEIGHT - GLYPH_HEIGHT * TEXT_SCALE) / 2;
+            draw_text(buffer, text_x, text_y, &text, TEXT_SCALE, TEXT_COLOR);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::export::write_ppm;
+use crate::render::{BarRenderer, SortView};
+use crate::{HEIGHT, WIDTH};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+
+// Destination for the frames of a video recording
+pub trait FrameSink {
+    // buffer: The pixel buffer (0xRRGGBB per pixel, WIDTH * HEIGHT pixels)
+    fn write_frame(&mut self, buffer: &[u32]) -> io::Result<()>;
+}
+
+// Writes the frames as an uncompressed YUV4MPEG2 stream (4:4:4, BT.601 limited range)
+// that ffmpeg can read from a file or a pipe
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    plane: Vec<u8>, // Reused buffer holding the Y, U and V planes of a frame
+}
+
+impl<W: Write> Y4mWriter<W> {
+    // writer: Where the stream is written
+    // fps: The frame rate stored in the stream header
+    pub fn new(mut writer: W, fps: u32) -> io::Result<Self> {
+        writeln!(
+            writer,
+            "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+            WIDTH, HEIGHT, fps
+        )?;
+        Ok(Y4mWriter {
+            writer,
+            plane: vec![0; WIDTH * HEIGHT * 3],
+        })
+    }
+
+    // Flushes the stream and hands back the writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush().map(|()| self.writer)
+    }
+}
+
+// Converts a 0xRRGGBB color to limited range BT.601 Y, U and V
+fn rgb_to_yuv(color: u32) -> [u8; 3] {
+    let r = ((color >> 16) & 0xFF) as i32;
+    let g = ((color >> 8) & 0xFF) as i32;
+    let b = (color & 0xFF) as i32;
+    let y = ((66 * r + 129 * g + 25 * b + 128) >> 8) + 16;
+    let u = ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128;
+    let v = ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128;
+    [y as u8, u as u8, v as u8]
+}
+
+impl<W: Write> FrameSink for Y4mWriter<W> {
+    fn write_frame(&mut self, buffer: &[u32]) -> io::Result<()> {
+        let size = WIDTH * HEIGHT;
+        // Frames only use a handful of colors, so convert each run of equal pixels once
+        let mut last = (u32::MAX, [0; 3]);
+        for (i, &pixel) in buffer.iter().enumerate() {
+            if pixel != last.0 {
+                last = (pixel, rgb_to_yuv(pixel));
+            }
+            self.plane[i] = last.1[0];
+            self.plane[size + i] = last.1[1];
+            self.plane[2 * size + i] = last.1[2];
+        }
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&self.plane)
+    }
+}
+
+// Writes every frame as a numbered PPM image (frame_000000.ppm, frame_000001.ppm, ...)
+// e.g. for `ffmpeg -framerate 30 -i frame_%06d.ppm`
+pub struct PpmSequence {
+    directory: PathBuf,
+    next: usize, // Number of the next frame file
+}
+
+impl PpmSequence {
+    // directory: Where the images are written (created if it does not exist)
+    pub fn new(directory: impl Into<PathBuf>) -> io::Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(PpmSequence { directory, next: 0 })
+    }
+}
+
+impl FrameSink for PpmSequence {
+    fn write_frame(&mut self, buffer: &[u32]) -> io::Result<()> {
+        let path = self.directory.join(format!("frame_{:06}.ppm", self.next));
+        let mut file = BufWriter::new(File::create(path)?);
+        write_ppm(&mut file, buffer)?;
+        self.next += 1;
+        file.flush()
+    }
+}
+
+// Settings for a video recording
+// fps: Frame rate of the video; every kept frame lasts 1/fps seconds
+// frame_step: Only every frame_step-th rendered frame is kept; 1 keeps every frame
+// hold_final_frames: How many times the finished array is repeated at the end
+#[derive(Debug, Clone, Copy)]
+pub struct VideoOptions {
+    pub fps: u32,
+    pub frame_step: usize,
+    pub hold_final_frames: usize,
+}
+
+impl Default for VideoOptions {
+    fn default() -> Self {
+        VideoOptions {
+            fps: 30,
+            frame_step: 1,
+            hold_final_frames: 30, // One second at the default frame rate
+        }
+    }
+}
+
+// Decides which rendered frames end up in a video and how long the final frame is held.
+// The video recorders and the WAV renderer share it so audio and video line up.
+#[derive(Debug, Clone)]
+pub struct VideoTimeline {
+    options: VideoOptions,
+    rendered: usize, // Number of frames rendered so far (kept or not)
+    written: usize,  // Number of frames in the video so far
+}
+
+impl VideoTimeline {
+    pub fn new(options: VideoOptions) -> Self {
+        VideoTimeline {
+            options: VideoOptions {
+                frame_step: options.frame_step.max(1),
+                hold_final_frames: options.hold_final_frames.max(1),
+                ..options
+            },
+            rendered: 0,
+            written: 0,
+        }
+    }
+
+    // Called for every rendered frame, returns how many times it appears in the video
+    // is_final: The frame shows the finished array
+    pub fn next_frame(&mut self, is_final: bool) -> usize {
+        let count = if is_final {
+            // The finished array is always part of the video, held for a moment
+            self.options.hold_final_frames
+        } else if self.rendered.is_multiple_of(self.options.frame_step) {
+            1
+        } else {
+            0
+        };
+        self.rendered += 1;
+        self.written += count;
+        count
+    }
+
+    // Number of frames in the video so far; it lasts frames_written() / fps seconds
+    pub fn frames_written(&self) -> usize {
+        self.written
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.options.fps
+    }
+}
+
+// Observer that renders the frames of a sort and writes them to a FrameSink at a fixed frame rate
+// The first write error is kept and reported by finish(), later frames are dropped
+pub struct VideoRecorder<S: FrameSink> {
+    sink: S,
+    renderer: BarRenderer,
+    timeline: VideoTimeline,
+    error: Option<io::Error>,
+}
+
+impl<S: FrameSink> VideoRecorder<S> {
+    // sink: Where the frames are written
+    // num_bars: The total number of bars
+    // bar_width: The calculated width of each bar
+    // options: Frame rate and decimation of the video
+    pub fn new(sink: S, num_bars: usize, bar_width: usize, options: VideoOptions) -> Self {
+        VideoRecorder {
+            sink,
+            renderer: BarRenderer::new(num_bars, bar_width),
+            timeline: VideoTimeline::new(options),
+            error: None,
+        }
+    }
+
+    // Draws the view of the algorithm (e.g. its auxiliary arrays) into every frame
+    pub fn set_view(&mut self, view: Box<dyn SortView>) {
+        self.renderer.set_view(view);
+    }
+
+    // Number of frames written so far; the video lasts frames_written() / fps seconds
+    pub fn frames_written(&self) -> usize {
+        self.timeline.frames_written()
+    }
+
+    // Hands back the frame sink
+    // Returns the first error that happened while writing the frames instead, if any
+    pub fn finish(self) -> io::Result<S> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.sink),
+        }
+    }
+
+    fn write_frame(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(error) = self.sink.write_frame(&self.renderer.buffer) {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl<S: FrameSink> SortObserver for VideoRecorder<S> {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        if self.error.is_some() || !self.renderer.apply(array, event) {
+            return ControlFlow::Continue(());
+        }
+        for _ in 0..self.timeline.next_frame(event == SortEvent::Done) {
+            self.write_frame();
+        }
+        ControlFlow::Continue(())
+    }
+}
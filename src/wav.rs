@@ -0,0 +1,111 @@
+use crate::events::{SortEvent, SortObserver};
+use crate::render::draws_frame;
+use crate::video::{VideoOptions, VideoTimeline};
+use crate::{tone_frequency, TONE_VOLUME};
+use std::f32::consts::TAU;
+use std::io::{Seek, Write};
+use std::ops::ControlFlow;
+
+// Sample rate of the rendered audio
+pub const WAV_SAMPLE_RATE: u32 = 44_100;
+
+// Observer that synthesizes the tones of a sort offline instead of playing them on a live Sink.
+// The audio follows the same timeline as a video recorded with the same VideoOptions:
+// the tones played since the previous video frame are spread over the time slot of the next one,
+// so the WAV file and the video can be muxed afterwards.
+pub struct WavRenderer {
+    timeline: VideoTimeline,
+    duration_ms: u64,
+    pending: Vec<(u32, usize)>, // Tones (value, num_bars) waiting for the next video frame
+    samples: Vec<f32>,
+}
+
+impl WavRenderer {
+    // options: The settings of the video the audio is aligned with
+    // duration_ms: The duration of each tone in milliseconds
+    pub fn new(options: VideoOptions, duration_ms: u64) -> Self {
+        WavRenderer {
+            timeline: VideoTimeline::new(options),
+            duration_ms,
+            pending: Vec::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    // The audio rendered so far, as samples between -1.0 and 1.0 (mono, WAV_SAMPLE_RATE)
+    // It lasts exactly as long as the video, tones still ringing at the end are cut off
+    pub fn samples(&self) -> &[f32] {
+        &self.samples[..self.frame_start(self.timeline.frames_written())]
+    }
+
+    // Writes the audio as a mono 16-bit PCM WAV file
+    pub fn write<W: Write + Seek>(&self, writer: W) -> hound::Result<()> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: WAV_SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut wav = hound::WavWriter::new(writer, spec)?;
+        for &sample in self.samples() {
+            wav.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+        }
+        wav.finalize()
+    }
+
+    // First sample of the given video frame
+    fn frame_start(&self, frame: usize) -> usize {
+        (frame as u64 * WAV_SAMPLE_RATE as u64 / self.timeline.fps() as u64) as usize
+    }
+
+    // Mixes a sine tone into the samples
+    fn add_tone(&mut self, start: usize, value: u32, num_bars: usize) {
+        let freq = tone_frequency(value, num_bars);
+        let length = (self.duration_ms * WAV_SAMPLE_RATE as u64 / 1000) as usize;
+        if self.samples.len() < start + length {
+            self.samples.resize(start + length, 0.0);
+        }
+        for (i, sample) in self.samples[start..start + length].iter_mut().enumerate() {
+            let t = i as f32 / WAV_SAMPLE_RATE as f32;
+            *sample += (TAU * freq * t).sin() * TONE_VOLUME;
+        }
+    }
+}
+
+impl SortObserver for WavRenderer {
+    fn on_event(&mut self, array: &[u32], event: SortEvent) -> ControlFlow<()> {
+        // The same tones ToneObserver would play live
+        match event {
+            SortEvent::Compare(a, _) | SortEvent::Swap(a, _) | SortEvent::Read(a) => {
+                self.pending.push((array[a], array.len()))
+            }
+            SortEvent::Write(_, value) => self.pending.push((value, array.len())),
+            SortEvent::AuxCompare(_, _, value, index) => {
+                self.pending.push((value.min(array[index]), array.len()))
+            }
+            _ => {}
+        }
+        if !draws_frame(event) {
+            return ControlFlow::Continue(());
+        }
+
+        let first_frame = self.timeline.frames_written();
+        if self.timeline.next_frame(event == SortEvent::Done) == 0 {
+            // The frame is not part of the video, its tones move on to the next one
+            return ControlFlow::Continue(());
+        }
+        // Spread the pending tones evenly over the time slot of the frame
+        let slot_start = self.frame_start(first_frame);
+        let slot_length = self.frame_start(first_frame + 1) - slot_start;
+        let tones = std::mem::take(&mut self.pending);
+        for (i, &(value, num_bars)) in tones.iter().enumerate() {
+            self.add_tone(slot_start + i * slot_length / tones.len(), value, num_bars);
+        }
+        // Silence until the end of the frames written so far
+        let end = self.frame_start(self.timeline.frames_written());
+        if self.samples.len() < end {
+            self.samples.resize(end, 0.0);
+        }
+        ControlFlow::Continue(())
+    }
+}